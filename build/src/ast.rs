@@ -2,6 +2,7 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use std::boxed::Box;
 use std::collections::{HashMap, HashSet};
+use syn::ext::IdentExt;
 use syn::{Attribute, Ident, LitStr};
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -9,6 +10,8 @@ pub enum TypeKind {
     Primitive(String),
     String,
     Object(String),
+    /// A C++ enum declared with `enum` in the DSL, passed by value.
+    Enum(String),
     Vector {
         inner: Box<TypeKind>,
         is_ptr: bool,
@@ -52,9 +55,61 @@ impl TypeKind {
         TypeKind::Result(Box::new(inner))
     }
 
+    /// Replaces every `Self` inside the type with the enclosing class.
+    pub fn resolve_self(&mut self, class_name: &str) {
+        match self {
+            TypeKind::Object(s) if s == "Self" => *s = class_name.to_string(),
+            TypeKind::Vector { inner, .. }
+            | TypeKind::Reference { inner, .. }
+            | TypeKind::Option(inner)
+            | TypeKind::Result(inner)
+            | TypeKind::UniquePtr(inner)
+            | TypeKind::Slice(inner) => inner.resolve_self(class_name),
+            TypeKind::Map { key, value, .. } => {
+                key.resolve_self(class_name);
+                value.resolve_self(class_name);
+            }
+            TypeKind::Primitive(_)
+            | TypeKind::String
+            | TypeKind::Object(_)
+            | TypeKind::Enum(_) => {}
+        }
+    }
+
+    /// Turns the class-like names of declared enums into `TypeKind::Enum`.
+    pub fn resolve_enums(&mut self, enums: &HashSet<String>) {
+        match self {
+            TypeKind::Object(s) if enums.contains(s) => *self = TypeKind::Enum(s.clone()),
+            TypeKind::Vector { inner, .. }
+            | TypeKind::Reference { inner, .. }
+            | TypeKind::Option(inner)
+            | TypeKind::Result(inner)
+            | TypeKind::UniquePtr(inner)
+            | TypeKind::Slice(inner) => inner.resolve_enums(enums),
+            TypeKind::Map { key, value, .. } => {
+                key.resolve_enums(enums);
+                value.resolve_enums(enums);
+            }
+            TypeKind::Primitive(_)
+            | TypeKind::String
+            | TypeKind::Object(_)
+            | TypeKind::Enum(_) => {}
+        }
+    }
+
     pub fn to_ffi_type(&self, is_return: bool) -> TokenStream {
+        self.ffi_type(is_return, None)
+    }
+
+    /// Like `to_ffi_type(true)`, but ties any returned reference to `lt` so
+    /// that cxx can tell which argument it borrows from.
+    pub fn to_ffi_ret_type_with_lifetime(&self, lt: &TokenStream) -> TokenStream {
+        self.ffi_type(true, Some(lt))
+    }
+
+    fn ffi_type(&self, is_return: bool, lt: Option<&TokenStream>) -> TokenStream {
         match self {
-            TypeKind::Primitive(s) => {
+            TypeKind::Primitive(s) | TypeKind::Enum(s) => {
                 let i = format_ident!("{}", s);
                 quote! { #i }
             }
@@ -80,25 +135,38 @@ impl TypeKind {
 
             TypeKind::Reference { inner, is_mut } => {
                 if let TypeKind::Slice(elem) = &**inner {
+                    match (&**elem, *is_mut) {
+                        (TypeKind::String, false) => return quote! { &#lt [&str] },
+                        (TypeKind::Object(_), false) => {
+                            let t = elem.slice_vec_type().to_ffi_type_name_only();
+                            return quote! { &#lt #t };
+                        }
+                        (TypeKind::String | TypeKind::Object(_), true) => {
+                            panic!("Mutable slices are only supported for primitives: {:?}", self)
+                        }
+                        _ => {}
+                    }
                     let t = elem.to_ffi_type_name_only();
                     return if *is_mut {
-                        quote! { &mut [#t] }
+                        quote! { &#lt mut [#t] }
                     } else {
-                        quote! { &[#t] }
+                        quote! { &#lt [#t] }
                     };
                 }
 
-                if let TypeKind::String = &**inner {
-                    if !*is_mut {
-                        return quote! { &str };
-                    }
+                if let TypeKind::String = &**inner
+                    && !*is_mut
+                {
+                    return quote! { &#lt str };
                 }
 
                 let t = inner.to_ffi_type_name_only();
-                if *is_mut {
-                    quote! { Pin<&mut #t> }
+                if *is_mut && let TypeKind::Primitive(_) = &**inner {
+                    quote! { &#lt mut #t }
+                } else if *is_mut {
+                    quote! { Pin<&#lt mut #t> }
                 } else {
-                    quote! { &#t }
+                    quote! { &#lt #t }
                 }
             }
 
@@ -108,9 +176,9 @@ impl TypeKind {
                 }
 
                 if inner.is_object_value() {
-                    inner.to_ffi_type(true)
+                    inner.ffi_type(true, lt)
                 } else {
-                    let t = inner.to_ffi_type(true);
+                    let t = inner.ffi_type(true, lt);
                     quote! { Result<#t> }
                 }
             }
@@ -129,15 +197,23 @@ impl TypeKind {
                 if !is_return {
                     panic!("Result type is not supported as function argument in FFI");
                 }
-                let inner_ty = inner.to_ffi_type(is_return);
+                let inner_ty = inner.ffi_type(is_return, lt);
                 quote! { Result<#inner_ty> }
             }
         }
     }
 
+    pub fn returns_reference(&self) -> bool {
+        match self {
+            TypeKind::Reference { .. } => true,
+            TypeKind::Option(inner) => inner.returns_reference(),
+            _ => false,
+        }
+    }
+
     pub fn to_ffi_type_name_only(&self) -> TokenStream {
         match self {
-            TypeKind::Primitive(s) => {
+            TypeKind::Primitive(s) | TypeKind::Enum(s) => {
                 let i = format_ident!("{}", s);
                 quote! {#i}
             }
@@ -155,6 +231,31 @@ impl TypeKind {
         }
     }
 
+    /// The `std::vector` a slice of this element is copied into before the
+    /// call, when the element cannot cross the bridge as a slice.
+    pub fn slice_vec_type(&self) -> TypeKind {
+        TypeKind::Vector {
+            inner: Box::new(self.clone()),
+            is_ptr: false,
+        }
+    }
+
+    /// `std::vector<bool>` is bit-packed and needs its own shims.
+    pub fn is_bool(&self) -> bool {
+        matches!(self, TypeKind::Primitive(p) if p == "bool")
+    }
+
+    /// Primitives and enums are plain values that containers can lend out
+    /// in place, as `&mut T` or slices.
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, TypeKind::Primitive(_) | TypeKind::Enum(_))
+    }
+
+    /// Primitives and strings can cross the bridge inside a `rust::Vec`.
+    pub fn is_bulk_copyable(&self) -> bool {
+        matches!(self, TypeKind::Primitive(_) | TypeKind::String)
+    }
+
     pub fn is_object_value(&self) -> bool {
         matches!(
             self,
@@ -162,9 +263,37 @@ impl TypeKind {
         )
     }
 
+    /// The type as written in the DSL, for `FieldInfo::ty`.
+    pub fn describe(&self) -> String {
+        let boxed = |inner: &TypeKind, is_ptr: bool| {
+            if is_ptr {
+                format!("UniquePtr<{}>", inner.describe())
+            } else {
+                inner.describe()
+            }
+        };
+        match self {
+            TypeKind::Primitive(s) | TypeKind::Object(s) | TypeKind::Enum(s) => s.clone(),
+            TypeKind::String => "String".to_string(),
+            TypeKind::Vector { inner, is_ptr } => format!("Vec<{}>", boxed(inner, *is_ptr)),
+            TypeKind::Map {
+                key,
+                value,
+                is_val_ptr,
+            } => format!("Map<{}, {}>", key.describe(), boxed(value, *is_val_ptr)),
+            TypeKind::Option(inner) => format!("Option<{}>", inner.describe()),
+            TypeKind::Result(inner) => format!("Result<{}>", inner.describe()),
+            TypeKind::UniquePtr(inner) => format!("UniquePtr<{}>", inner.describe()),
+            TypeKind::Reference { inner, is_mut } => {
+                format!("&{}{}", if *is_mut { "mut " } else { "" }, inner.describe())
+            }
+            TypeKind::Slice(inner) => format!("[{}]", inner.describe()),
+        }
+    }
+
     pub fn get_flat_name(&self) -> String {
         match self {
-            TypeKind::Primitive(s) | TypeKind::Object(s) => s.clone(),
+            TypeKind::Primitive(s) | TypeKind::Object(s) | TypeKind::Enum(s) => s.clone(),
             TypeKind::String => "String".to_string(),
 
             TypeKind::Vector { inner, is_ptr } => {
@@ -199,7 +328,13 @@ impl TypeKind {
         match self {
             TypeKind::Reference { inner, is_mut } => {
                 if let TypeKind::Slice(slice_inner) = &**inner {
-                    let t = slice_inner.to_rust_wrapper_arg_type();
+                    let t = match &**slice_inner {
+                        TypeKind::Object(_) => {
+                            let tag = slice_inner.to_rust_tag();
+                            quote! { justcxx::CppRef<'_, #tag> }
+                        }
+                        _ => slice_inner.to_rust_wrapper_arg_type(),
+                    };
                     return if *is_mut {
                         quote! { &mut [#t] }
                     } else {
@@ -208,7 +343,11 @@ impl TypeKind {
                 }
 
                 if let TypeKind::String = **inner {
-                    return quote! { &str };
+                    return if *is_mut {
+                        quote! { &mut String }
+                    } else {
+                        quote! { &str }
+                    };
                 }
 
                 if inner.is_object_value() {
@@ -233,7 +372,7 @@ impl TypeKind {
                 quote! { justcxx::CppOwned<#tag> }
             }
 
-            TypeKind::Primitive(s) => {
+            TypeKind::Primitive(s) | TypeKind::Enum(s) => {
                 let i = format_ident!("{}", s);
                 quote! {#i}
             }
@@ -285,7 +424,7 @@ impl TypeKind {
             }
 
             TypeKind::String => quote! { String },
-            TypeKind::Primitive(s) => {
+            TypeKind::Primitive(s) | TypeKind::Enum(s) => {
                 let i = format_ident!("{}", s);
                 quote! {#i}
             }
@@ -297,6 +436,20 @@ impl TypeKind {
     pub fn gen_arg_conversion(&self, arg_name: &Ident) -> TokenStream {
         match self {
             TypeKind::Reference { inner, is_mut } => {
+                if let TypeKind::Slice(elem) = &**inner
+                    && let TypeKind::Object(_) = &**elem
+                {
+                    // the C++ side needs the objects side by side, so copy them;
+                    // `CppRef` is a transparent pointer, the slice goes over as is
+                    let from_refs_fn = get_from_refs_fn_name(&elem.slice_vec_type());
+                    let t = elem.to_ffi_type_name_only();
+                    return quote! {
+                        &*ffi::#from_refs_fn(std::slice::from_raw_parts(
+                            #arg_name.as_ptr() as *const *const ffi::#t,
+                            #arg_name.len(),
+                        ))
+                    };
+                }
                 if inner.is_object_value() {
                     if *is_mut {
                         quote! { std::pin::Pin::new_unchecked(&mut *#arg_name.as_ptr()) }
@@ -359,7 +512,7 @@ impl TypeKind {
 
     pub fn to_rust_tag(&self) -> TokenStream {
         match self {
-            TypeKind::Object(s) | TypeKind::Primitive(s) => {
+            TypeKind::Object(s) | TypeKind::Primitive(s) | TypeKind::Enum(s) => {
                 let i = format_ident!("{}", s);
                 quote! { #i }
             }
@@ -398,12 +551,48 @@ impl TypeKind {
 #[derive(Clone, Debug)]
 pub struct FieldDef {
     pub name: Ident,
+    /// The C++ data member, `name` unless `#[rename]`d.
+    pub cpp_name: String,
     pub ty: TypeKind,
     pub is_protected: bool,
     pub is_readonly: bool,
+    /// `#[get = "..", set = ".."]`: reached through C++ methods instead of a member.
+    pub accessors: Option<FieldAccessors>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldAccessors {
+    pub get: String,
+    pub set: Option<String>,
 }
 
 impl FieldDef {
+    /// Whether the C++ side hands out `T&`; accessor getters are taken as const.
+    pub fn has_mut_getter(&self) -> bool {
+        !self.is_readonly && self.accessors.is_none()
+    }
+
+    /// Whether the wrapper has a getter at all; types such as `UniquePtr` are
+    /// bound without one.
+    pub fn has_getter(&self) -> bool {
+        match &self.ty {
+            TypeKind::Primitive(_)
+            | TypeKind::String
+            | TypeKind::Enum(_)
+            | TypeKind::Object(_)
+            | TypeKind::Map { .. }
+            | TypeKind::Vector { .. }
+            | TypeKind::Option(_) => true,
+            TypeKind::Reference { inner, .. } => matches!(**inner, TypeKind::Slice(_)),
+            _ => false,
+        }
+    }
+
+    /// Whether the wrapper has a `set_{name}`; `#[contiguous]` slices have none.
+    pub fn has_setter(&self) -> bool {
+        !self.is_readonly && !matches!(self.ty, TypeKind::Reference { .. })
+    }
+
     pub fn get_ffi_get_name(&self, class_name: &Ident) -> Ident {
         format_ident!("{}_get_{}", class_name, self.name)
     }
@@ -415,12 +604,93 @@ impl FieldDef {
     pub fn get_wrapper_set_name(&self) -> Ident {
         format_ident!("set_{}", self.name)
     }
+
+    /// `Option` fields are reset to empty through `clear_{name}`.
+    pub fn get_ffi_clear_name(&self, class_name: &Ident) -> Ident {
+        format_ident!("{}_clear_{}", class_name, self.name)
+    }
+
+    pub fn get_wrapper_clear_name(&self) -> Ident {
+        format_ident!("clear_{}", self.name)
+    }
+
+    pub fn init_arg(&self) -> Option<InitArg> {
+        match &self.ty {
+            TypeKind::Primitive(_) | TypeKind::String => Some(InitArg::Value),
+            TypeKind::Object(_) | TypeKind::Vector { .. } | TypeKind::Map { .. } => {
+                Some(InitArg::Object)
+            }
+            TypeKind::Option(inner) => match **inner {
+                TypeKind::Primitive(_) | TypeKind::String => Some(InitArg::Optional),
+                TypeKind::Object(_) | TypeKind::Vector { .. } | TypeKind::Map { .. } => {
+                    Some(InitArg::Object)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// How a field of a `#[derive(Init)]` struct reaches `{Class}_from_init`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InitArg {
+    /// A primitive or string, passed as is.
+    Value,
+    /// `Option<primitive | String>`, passed as a presence flag and a value.
+    Optional,
+    /// An object, possibly optional, passed as a `UniquePtr` that is
+    /// value-initialized (or `std::nullopt`) when null.
+    Object,
+}
+
+pub fn get_init_struct_name(class_name: &Ident) -> Ident {
+    format_ident!("{}Init", class_name)
+}
+
+pub fn get_from_init_fn_name(class_name: &Ident) -> Ident {
+    format_ident!("{}_from_init", class_name)
+}
+
+/// `Canvas::resize_with` takes its defaulted arguments as `CanvasResizeArgs`.
+pub fn get_default_args_struct_name(class_name: &Ident, rust_name: &Ident) -> Ident {
+    let method: String = rust_name
+        .unraw()
+        .to_string()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    format_ident!("{}{}Args", class_name, method)
+}
+
+pub fn get_clone_fn_name(ty: &TypeKind) -> Ident {
+    format_ident!("{}_clone", ty.get_flat_name())
+}
+
+/// Builds the `std::vector` behind an object slice argument in one call.
+pub fn get_from_refs_fn_name(ty: &TypeKind) -> Ident {
+    format_ident!("{}_from_refs", ty.get_flat_name())
+}
+
+pub fn get_to_string_fn_name(class_name: &Ident) -> Ident {
+    format_ident!("{}_to_string", class_name)
+}
+
+pub fn get_from_str_fn_name(class_name: &Ident) -> Ident {
+    format_ident!("{}_from_str", class_name)
 }
 
 #[derive(Clone, Debug)]
 pub struct Arg {
     pub name: Ident,
     pub ty: TypeKind,
+    pub default: Option<syn::Expr>,
+    pub is_out: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -437,8 +707,45 @@ pub struct FnDef {
 pub struct IterDef {
     pub rust_name: Ident,
     pub yield_ty: TypeKind,
-    pub cpp_name: String,
-    pub is_iter_mut: bool,
+    pub range: IterRange,
+    pub mode: IterMode,
+    pub double_ended: bool,
+    pub is_protected: bool,
+}
+
+/// Where an `#[iter]` method finds its elements, as C++ source.
+#[derive(Clone, Debug)]
+pub struct IterRange {
+    /// Expression over `obj` giving the range, `obj` itself by default.
+    pub expr: String,
+    /// The class member `expr` reads, if it names one.
+    pub member: Option<String>,
+    pub begin: String,
+    pub end: String,
+}
+
+impl Default for IterRange {
+    fn default() -> Self {
+        Self {
+            expr: "obj".to_string(),
+            member: None,
+            begin: "begin".to_string(),
+            end: "end".to_string(),
+        }
+    }
+}
+
+/// How an `#[iter]` method hands out the container's elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IterMode {
+    /// `Item = &T`: borrowed from `&self`.
+    Ref,
+    /// `Item = &mut T`: borrowed from `&mut self`.
+    Mut,
+    /// `#[iter(drain)]`: every element is moved out.
+    Drain,
+    /// `Item = T` for primitives, strings and enums: copied out of `&self`.
+    Copy,
 }
 
 #[derive(Clone, Debug)]
@@ -447,6 +754,27 @@ pub struct CtorDef {
     pub args: Vec<Arg>,
     pub cpp_name: Ident,
     pub is_user_defined: bool,
+    pub is_protected: bool,
+    pub on_failure: CtorFailure,
+}
+
+/// How a constructor reports a throwing C++ constructor or factory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CtorFailure {
+    /// `-> Self`: the exception aborts, as with any other call.
+    Abort,
+    /// `-> Result<Self>`: exceptions and null factory results become `Err`.
+    Result,
+    /// `-> Option<Self>`: exceptions and null factory results become `None`.
+    Option,
+}
+
+impl CtorDef {
+    /// Factories go through a C++ shim when their result has to be converted
+    /// into the `_Exposer` or checked for null before it can become an `Err`.
+    pub fn needs_factory_shim(&self, needs_exposer: bool) -> bool {
+        self.is_user_defined && (needs_exposer || self.on_failure == CtorFailure::Result)
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -454,6 +782,7 @@ pub enum MethodKind {
     Static,
     Const,
     Mutable,
+    Consuming,
 }
 
 #[derive(Clone, Debug)]
@@ -463,11 +792,74 @@ pub enum MethodDef {
     Method(FnDef),
 }
 
+impl MethodDef {
+    /// Marked `#[protected]`, so only reachable through the `_Exposer` subclass.
+    pub fn is_protected(&self) -> bool {
+        match self {
+            MethodDef::Ctor(ctor) => ctor.is_protected,
+            MethodDef::Iter(iter) => iter.is_protected,
+            MethodDef::Method(func) => func.is_protected,
+        }
+    }
+
+    pub fn rust_name(&self) -> &Ident {
+        match self {
+            MethodDef::Ctor(ctor) => &ctor.rust_name,
+            MethodDef::Iter(iter) => &iter.rust_name,
+            MethodDef::Method(func) => &func.rust_name,
+        }
+    }
+
+    /// Has defaulted arguments, so it is also generated as `{name}_with`.
+    pub fn has_default_args(&self) -> bool {
+        let args = match self {
+            MethodDef::Ctor(ctor) => &ctor.args,
+            MethodDef::Iter(_) => return false,
+            MethodDef::Method(func) => &func.args,
+        };
+        args.iter().any(|arg| arg.default.is_some())
+    }
+
+    pub fn resolve_self(&mut self, class_name: &str) {
+        match self {
+            MethodDef::Ctor(ctor) => {
+                for arg in &mut ctor.args {
+                    arg.ty.resolve_self(class_name);
+                }
+            }
+            MethodDef::Iter(iter) => iter.yield_ty.resolve_self(class_name),
+            MethodDef::Method(func) => {
+                for arg in &mut func.args {
+                    arg.ty.resolve_self(class_name);
+                }
+                if let Some(ret) = &mut func.ret_ty {
+                    ret.resolve_self(class_name);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct StructDef {
     pub attrs: Vec<Attribute>,
     pub name: Ident,
     pub fields: Vec<FieldDef>,
+    pub derives: Vec<Ident>,
+    pub debug_protected: bool,
+    pub is_display: bool,
+    pub is_from_str: bool,
+    pub contiguous: Option<ContiguousDef>,
+    /// `#[opaque]` / `#[no_ctor]`: no `new()` is generated without a declared constructor.
+    pub no_default_ctor: bool,
+}
+
+/// `#[contiguous(elem = T)]`: the class stores `T`s contiguously behind
+/// `data()`/`size()`, and `resize(n)` too when `resize` is given.
+#[derive(Clone, Debug)]
+pub struct ContiguousDef {
+    pub elem: TypeKind,
+    pub resize: bool,
 }
 
 #[derive(Debug)]
@@ -480,9 +872,20 @@ pub struct ImplDef {
 pub enum BindItem {
     Include(LitStr),
     Struct(StructDef),
+    Enum(EnumDef),
     Impl(ImplDef),
 }
 
+/// `enum Color { Red, Green }`: an existing C++ enum, shared with Rust through
+/// cxx, which checks every discriminant against the C++ definition.
+#[derive(Clone, Debug)]
+pub struct EnumDef {
+    pub name: Ident,
+    /// The underlying type from `#[repr(..)]`, `i32` by default like `enum class`.
+    pub repr: Ident,
+    pub variants: Vec<syn::Variant>,
+}
+
 #[derive(Debug)]
 pub struct BindInput {
     pub items: Vec<BindItem>,
@@ -493,7 +896,34 @@ pub struct ClassModel {
     pub name: Ident,
     pub fields: Vec<FieldDef>,
     pub methods: Vec<MethodDef>,
+    pub derives: Vec<Ident>,
+    pub debug_protected: bool,
+    pub is_display: bool,
+    pub is_from_str: bool,
+    pub contiguous: Option<ContiguousDef>,
+    pub no_default_ctor: bool,
     pub needs_exposer: bool,
+    /// Fields carried by the `#[derive(Mirror)]` data struct, empty without it.
+    pub mirror_fields: Vec<MirrorField>,
+}
+
+/// A field of a `#[derive(Mirror)]` class copied into its `{Class}Data`.
+#[derive(Clone, Debug)]
+pub struct MirrorField {
+    pub field: FieldDef,
+    /// The mirrored class of an object field, copied through its own data struct.
+    pub nested: Option<Ident>,
+}
+
+pub fn get_mirror_data_name(class_name: &Ident) -> Ident {
+    format_ident!("{}Data", class_name)
+}
+
+pub fn get_mirror_fn_names(class_name: &Ident) -> (Ident, Ident) {
+    (
+        format_ident!("{}_mirror_snapshot", class_name),
+        format_ident!("{}_mirror_apply", class_name),
+    )
 }
 
 impl ClassModel {
@@ -502,9 +932,29 @@ impl ClassModel {
             name,
             fields: Vec::new(),
             methods: Vec::new(),
+            derives: Vec::new(),
+            debug_protected: false,
+            is_display: false,
+            is_from_str: false,
+            contiguous: None,
+            no_default_ctor: false,
             needs_exposer: false,
+            mirror_fields: Vec::new(),
         }
     }
+
+    pub fn has_derive(&self, name: &str) -> bool {
+        self.derives.iter().any(|d| d == name)
+    }
+
+    /// Whether `new()` builds the object without arguments or a way to fail.
+    pub fn has_default_new(&self) -> bool {
+        self.methods.iter().any(|m| {
+            matches!(m, MethodDef::Ctor(c)
+                if c.rust_name == "new" && c.args.is_empty() && c.on_failure == CtorFailure::Abort)
+        })
+    }
+
     pub fn get_cxx_name(&self) -> Ident {
         if self.needs_exposer {
             format_ident!("{}_Exposer", self.name)
@@ -520,6 +970,8 @@ pub struct IterNames {
     pub ctx_name: Ident,
     pub new_fn: Ident,
     pub next_fn: Ident,
+    pub done_fn: Ident,
+    pub next_back_fn: Ident,
 }
 
 impl IterNames {
@@ -529,6 +981,8 @@ impl IterNames {
             ctx_name: format_ident!("{}_{}_IterCtx", class_name, method_name),
             new_fn: format_ident!("{}_{}_iter_new", class_name, method_name),
             next_fn: format_ident!("{}_{}_iter_next", class_name, method_name),
+            done_fn: format_ident!("{}_{}_iter_done", class_name, method_name),
+            next_back_fn: format_ident!("{}_{}_iter_next_back", class_name, method_name),
         }
     }
 }
@@ -537,6 +991,88 @@ pub struct BindContext {
     pub includes: Vec<syn::LitStr>,
     pub models: HashMap<String, ClassModel>,
     pub class_names_order: Vec<String>,
+    pub enums: Vec<EnumDef>,
     pub vec_defs: HashSet<TypeKind>,
+    /// The `Vec`s built from object slice arguments.
+    pub slice_vec_defs: HashSet<TypeKind>,
     pub map_defs: HashSet<TypeKind>,
 }
+
+impl BindContext {
+    /// Whether a deep copy of `ty` can be made through its C++ copy constructor.
+    pub fn is_cloneable(&self, ty: &TypeKind) -> bool {
+        match ty {
+            TypeKind::Primitive(_) | TypeKind::String | TypeKind::Enum(_) => true,
+            TypeKind::Object(name) => self
+                .models
+                .get(name)
+                .is_some_and(|m| m.has_derive("Clone")),
+            TypeKind::Vector { inner, is_ptr } => !*is_ptr && self.is_cloneable(inner),
+            TypeKind::Map {
+                key,
+                value,
+                is_val_ptr,
+            } => !*is_val_ptr && self.is_cloneable(key) && self.is_cloneable(value),
+            _ => false,
+        }
+    }
+
+    /// Whether a value of `ty` can be built from scratch, as `FromValue` does.
+    pub fn is_constructible(&self, ty: &TypeKind) -> bool {
+        match ty {
+            TypeKind::Primitive(_) | TypeKind::String | TypeKind::Enum(_) => true,
+            TypeKind::Object(name) => self.models.get(name).is_some_and(|m| m.has_default_new()),
+            TypeKind::Vector { inner, .. } => self.is_constructible(inner),
+            TypeKind::Map { key, value, .. } => {
+                self.is_constructible(key) && self.is_constructible(value)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether any bound class derives `name`.
+    pub fn has_derive(&self, name: &str) -> bool {
+        self.models.values().any(|m| m.has_derive(name))
+    }
+
+    /// Whether `ty` can be written out through its getters with serde.
+    pub fn is_serializable(&self, ty: &TypeKind) -> bool {
+        match ty {
+            TypeKind::Primitive(_) | TypeKind::String => true,
+            TypeKind::Object(name) => self
+                .models
+                .get(name)
+                .is_some_and(|m| m.has_derive("Serialize")),
+            TypeKind::Vector { inner, .. } | TypeKind::Option(inner) => {
+                self.is_serializable(inner)
+            }
+            TypeKind::Map { key, value, .. } => {
+                self.is_serializable(key) && self.is_serializable(value)
+            }
+            TypeKind::Reference { inner, .. } => match &**inner {
+                TypeKind::Slice(elem) => self.is_serializable(elem),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether `ty` can be read back with serde into a freshly constructed
+    /// value; map keys have to be primitives or strings.
+    pub fn is_deserializable(&self, ty: &TypeKind) -> bool {
+        match ty {
+            TypeKind::Primitive(_) | TypeKind::String => true,
+            TypeKind::Object(name) => self
+                .models
+                .get(name)
+                .is_some_and(|m| m.has_derive("Deserialize")),
+            TypeKind::Vector { inner, .. } => self.is_deserializable(inner),
+            TypeKind::Map { key, value, .. } => {
+                matches!(**key, TypeKind::Primitive(_) | TypeKind::String)
+                    && self.is_deserializable(value)
+            }
+            TypeKind::Option(inner) => self.is_deserializable(inner),
+            _ => false,
+        }
+    }
+}
@@ -1,13 +1,33 @@
 use crate::ast::*;
-use std::collections::HashSet;
 
 pub fn generate_cpp(bind_context: &BindContext) -> String {
     let mut lines = Vec::new();
     generate_includes(&bind_context.includes, &mut lines);
     lines.push("".to_string());
 
-    generate_vec_shims(&bind_context.vec_defs, &mut lines);
-    generate_map_shims(&bind_context.map_defs, &mut lines);
+    // must precede every shim that could convert these types
+    for class_name_str in &bind_context.class_names_order {
+        if bind_context.models[class_name_str].contiguous.is_some() {
+            lines.push(format!("DEFINE_CONTIGUOUS({})", class_name_str));
+        }
+    }
+
+    // mirrors of nested objects may be defined after the class using them
+    for class_name_str in &bind_context.class_names_order {
+        if bind_context.models[class_name_str].has_derive("Mirror") {
+            lines.push(format!(
+                "template <typename Data, typename Obj> Data {}_mirror_snapshot(const Obj &obj);",
+                class_name_str
+            ));
+            lines.push(format!(
+                "template <typename Obj, typename Data> void {}_mirror_apply(Obj &obj, const Data &data);",
+                class_name_str
+            ));
+        }
+    }
+
+    generate_vec_shims(bind_context, &mut lines);
+    generate_map_shims(bind_context, &mut lines);
 
     for class_name_str in &bind_context.class_names_order {
         let class = bind_context.models.get(class_name_str).unwrap();
@@ -40,7 +60,29 @@ fn generate_class_shim(class: &ClassModel, lines: &mut Vec<String>) {
     }
 
     for method in &class.methods {
-        generate_method_shim(&target_class_name, method, lines);
+        generate_method_shim(&target_class_name, class.needs_exposer, method, lines);
+    }
+
+    if let Some(contiguous) = &class.contiguous {
+        lines.push(format!("DEFINE_CONTIGUOUS_OPS({})", target_class_name));
+        if contiguous.resize {
+            lines.push(format!("DEFINE_CONTIGUOUS_RESIZE({})", target_class_name));
+        }
+    }
+    if class.has_derive("Clone") {
+        lines.push(format!("DEFINE_CLONE({})", target_class_name));
+    }
+    if class.has_derive("Init") {
+        generate_from_init(class, &original_class_name, &target_class_name, lines);
+    }
+    if class.has_derive("Mirror") {
+        generate_mirror_shims(class, lines);
+    }
+    if class.is_display {
+        lines.push(format!("DEFINE_DISPLAY({})", target_class_name));
+    }
+    if class.is_from_str {
+        lines.push(format!("DEFINE_FROM_STR({})", target_class_name));
     }
 }
 
@@ -53,61 +95,236 @@ fn generate_exposer_class(class: &ClassModel, original_name: &str, lines: &mut V
     lines.push("public:".to_string());
     lines.push(format!("using {}::{};", original_name, original_name));
 
-    for field in &class.fields {
-        if field.is_protected {
-            lines.push(format!("using {}::{};", original_name, field.name));
+    for field in class.fields.iter().filter(|f| f.is_protected) {
+        match &field.accessors {
+            Some(accessors) => {
+                lines.push(format!("using {}::{};", original_name, accessors.get));
+                if let Some(set) = &accessors.set {
+                    lines.push(format!("using {}::{};", original_name, set));
+                }
+            }
+            None => lines.push(format!("using {}::{};", original_name, field.cpp_name)),
         }
     }
 
-    for method in &class.methods {
-        if let MethodDef::Method(func) = method {
-            if func.is_protected {
+    let mut has_protected_ctor = false;
+    for method in class.methods.iter().filter(|m| m.is_protected()) {
+        match method {
+            MethodDef::Method(func) => {
                 lines.push(format!("using {}::{};", original_name, func.cpp_name));
             }
+            MethodDef::Ctor(ctor) if ctor.is_user_defined => {
+                lines.push(format!("using {}::{};", original_name, ctor.cpp_name));
+            }
+            MethodDef::Ctor(_) => has_protected_ctor = true,
+            MethodDef::Iter(iter) => match &iter.range.member {
+                Some(member) => lines.push(format!("using {}::{};", original_name, member)),
+                None => {
+                    lines.push(format!("using {}::{};", original_name, iter.range.begin));
+                    lines.push(format!("using {}::{};", original_name, iter.range.end));
+                }
+            },
         }
     }
 
+    // inherited constructors keep their access, so protected ones are
+    // reached through a public constructor tagged apart from the others
+    if has_protected_ctor {
+        lines.push("template <typename... Args>".to_string());
+        lines.push(format!(
+            "{}(::bridge_detail::protected_ctor_t, Args &&...args) : {}(std::forward<Args>(args)...) {{}}",
+            exposer_name, original_name
+        ));
+    }
+
+    // factories hand out the base class, which is moved in; a template so
+    // that classes without a move constructor still compile
+    lines.push("template <typename Base>".to_string());
+    lines.push(format!(
+        "{}(::bridge_detail::from_base_t, Base &&base) : {}(std::forward<Base>(base)) {{}}",
+        exposer_name, original_name
+    ));
+
     lines.push("};".to_string());
     lines.push("".to_string());
 }
 
+/// `{Class}_from_init` builds the object in one call, with designated
+/// initializers where the compiler supports them.
+fn generate_from_init(class: &ClassModel, original: &str, target: &str, lines: &mut Vec<String>) {
+    let mut params = Vec::new();
+    let mut members = Vec::new();
+    for field in &class.fields {
+        let name = &field.name;
+        let member = format!("decltype({}::{})", original, field.cpp_name);
+        let (param_list, value) = match field.init_arg().unwrap() {
+            InitArg::Optional => (
+                vec![format!("bool {}_present", name), format!("A_{} {}", name, name)],
+                format!(
+                    "::bridge_detail::init_optional<{}>({}_present, std::move({}))",
+                    member, name, name
+                ),
+            ),
+            InitArg::Value | InitArg::Object => (
+                vec![format!("A_{} {}", name, name)],
+                format!("::bridge_detail::init_member<{}>(std::move({}))", member, name),
+            ),
+        };
+        params.extend(param_list);
+        members.push((field.cpp_name.as_str(), value));
+    }
+
+    let type_params: Vec<_> = class
+        .fields
+        .iter()
+        .map(|f| format!("typename A_{}", f.name))
+        .collect();
+    if !type_params.is_empty() {
+        lines.push(format!("template <{}>", type_params.join(", ")));
+    }
+    lines.push(format!(
+        "inline std::unique_ptr<{}> {}_from_init({}) {{",
+        target,
+        target,
+        params.join(", ")
+    ));
+    // positional initialization silently misassigns members declared in
+    // another order, so check the layout matches the DSL
+    lines.push("#if __cplusplus < 202002L".to_string());
+    lines.push("#pragma GCC diagnostic push".to_string());
+    lines.push("#pragma GCC diagnostic ignored \"-Winvalid-offsetof\"".to_string());
+    let mut prev: Option<&str> = None;
+    for field in &class.fields {
+        let member = field.cpp_name.as_str();
+        let check = match prev {
+            None => format!("offsetof({}, {}) == 0", original, member),
+            Some(prev) => format!(
+                "::bridge_detail::init_follows<decltype({o}::{p}), decltype({o}::{m})>(offsetof({o}, {p}), offsetof({o}, {m}))",
+                o = original,
+                p = prev,
+                m = member
+            ),
+        };
+        lines.push(format!(
+            "    static_assert({}, \"#[derive(Init)] on {}: '{}' is not the next member, declare the fields in C++ order or build as C++20\");",
+            check, class.name, field.name
+        ));
+        prev = Some(member);
+    }
+    lines.push("#pragma GCC diagnostic pop".to_string());
+    lines.push("#endif".to_string());
+    lines.push(format!("    return ::bridge_detail::into_exposer<{}>(std::make_unique<{}>({}{{", target, original, original));
+    lines.push("#if __cplusplus >= 202002L".to_string());
+    for (member, value) in &members {
+        lines.push(format!("        .{} = {},", member, value));
+    }
+    lines.push("#else".to_string());
+    for (_, value) in &members {
+        lines.push(format!("        {},", value));
+    }
+    lines.push("#endif".to_string());
+    lines.push("    }));".to_string());
+    lines.push("}".to_string());
+}
+
+/// `#[derive(Mirror)]` copies between the object and its `{Class}Data`.
+/// Both are template parameters: the data struct is only defined by cxx
+/// after this header, and `Obj` may be the `_Exposer`.
+fn generate_mirror_shims(class: &ClassModel, lines: &mut Vec<String>) {
+    let (snapshot_fn, apply_fn) = get_mirror_fn_names(&class.name);
+
+    lines.push("template <typename Data, typename Obj>".to_string());
+    lines.push(format!("Data {}(const Obj &obj) {{", snapshot_fn));
+    lines.push("    Data data{};".to_string());
+    for mirror in &class.mirror_fields {
+        let field = &mirror.field;
+        let value = match (&mirror.nested, &field.accessors) {
+            (Some(nested), _) => format!(
+                "{}_mirror_snapshot<decltype(data.{})>(obj.{})",
+                nested, field.name, field.cpp_name
+            ),
+            (None, Some(accessors)) => {
+                format!("::bridge_detail::return_convert(obj.{}())", accessors.get)
+            }
+            (None, None) => format!("::bridge_detail::return_convert(obj.{})", field.cpp_name),
+        };
+        lines.push(format!("    data.{} = {};", field.name, value));
+    }
+    lines.push("    return data;".to_string());
+    lines.push("}".to_string());
+
+    lines.push("template <typename Obj, typename Data>".to_string());
+    lines.push(format!("void {}(Obj &obj, const Data &data) {{", apply_fn));
+    for mirror in class.mirror_fields.iter().filter(|m| !m.field.is_readonly) {
+        let field = &mirror.field;
+        let line = match (&mirror.nested, &field.accessors) {
+            (Some(nested), _) => format!(
+                "{}_mirror_apply(obj.{}, data.{});",
+                nested, field.cpp_name, field.name
+            ),
+            (None, Some(accessors)) => format!(
+                "obj.{}(::bridge_detail::arg_convert(data.{}));",
+                accessors.set.as_ref().unwrap(),
+                field.name
+            ),
+            (None, None) => format!(
+                "obj.{} = ::bridge_detail::arg_convert(data.{});",
+                field.cpp_name, field.name
+            ),
+        };
+        lines.push(format!("    {}", line));
+    }
+    lines.push("}".to_string());
+}
+
 fn generate_field_shim(class_name: &str, field: &FieldDef, lines: &mut Vec<String>) {
+    let name = &field.name;
+    let member = &field.cpp_name;
+
+    if let Some(accessors) = &field.accessors {
+        lines.push(format!("DEFINE_GETTER({}, {}, {})", class_name, name, accessors.get));
+        if let Some(set) = &accessors.set {
+            lines.push(format!("DEFINE_SETTER({}, {}, {})", class_name, name, set));
+        }
+        return;
+    }
+
     match &field.ty {
-        TypeKind::Primitive(_) | TypeKind::String => {
-            lines.push(format!("DEFINE_VAL({}, {})", class_name, field.name));
+        TypeKind::Primitive(_) | TypeKind::String | TypeKind::Enum(_) => {
+            lines.push(format!("DEFINE_VAL({}, {}, {})", class_name, name, member));
             if !field.is_readonly {
-                lines.push(format!("DEFINE_VAL_SET({}, {})", class_name, field.name));
+                lines.push(format!("DEFINE_VAL_SET({}, {}, {})", class_name, name, member));
             }
         }
 
         TypeKind::Object(_) | TypeKind::Vector { .. } | TypeKind::Map { .. } => {
             if field.is_readonly {
-                lines.push(format!("DEFINE_OBJ_CONST({}, {})", class_name, field.name));
+                lines.push(format!("DEFINE_OBJ_CONST({}, {}, {})", class_name, name, member));
             } else {
-                lines.push(format!("DEFINE_OBJ({}, {})", class_name, field.name));
-                lines.push(format!("DEFINE_OBJ_SET({}, {})", class_name, field.name));
+                lines.push(format!("DEFINE_OBJ({}, {}, {})", class_name, name, member));
+                lines.push(format!("DEFINE_OBJ_SET({}, {}, {})", class_name, name, member));
             }
         }
 
         TypeKind::Option(inner) => {
-            match (inner.is_object_value(), field.is_readonly) {
-                (true, true) => lines.push(format!(
-                    "DEFINE_OPT_OBJ_CONST({}, {})",
-                    class_name, field.name
-                )),
-                (true, false) => {
-                    lines.push(format!("DEFINE_OPT_OBJ({}, {})", class_name, field.name))
-                }
-                (false, _) => lines.push(format!("DEFINE_OPT_VAL({}, {})", class_name, field.name)),
-            }
+            let macro_name = match (inner.is_object_value(), field.is_readonly) {
+                (true, true) => "DEFINE_OPT_OBJ_CONST",
+                (true, false) => "DEFINE_OPT_OBJ",
+                (false, _) => "DEFINE_OPT_VAL",
+            };
+            lines.push(format!("{}({}, {}, {})", macro_name, class_name, name, member));
             if !field.is_readonly {
-                lines.push(format!("DEFINE_OBJ_SET({}, {})", class_name, field.name));
+                lines.push(format!("DEFINE_OBJ_SET({}, {}, {})", class_name, name, member));
+                lines.push(format!("DEFINE_OPT_CLEAR({}, {}, {})", class_name, name, member));
             }
         }
 
-        TypeKind::Reference { inner, .. } => {
+        TypeKind::Reference { inner, is_mut } => {
             if let TypeKind::Slice(_) = &**inner {
-                lines.push(format!("DEFINE_VAL({}, {})", class_name, field.name));
+                lines.push(format!("DEFINE_VAL({}, {}, {})", class_name, name, member));
+                if *is_mut {
+                    lines.push(format!("DEFINE_SLICE_MUT({}, {}, {})", class_name, name, member));
+                }
             }
         }
 
@@ -115,15 +332,33 @@ fn generate_field_shim(class_name: &str, field: &FieldDef, lines: &mut Vec<Strin
     }
 }
 
-fn generate_method_shim(class_name: &str, method: &MethodDef, lines: &mut Vec<String>) {
+fn generate_method_shim(
+    class_name: &str,
+    is_exposer: bool,
+    method: &MethodDef,
+    lines: &mut Vec<String>,
+) {
     match method {
         MethodDef::Iter(iter) => {
+            let (macro_name, back_macro) = match iter.mode {
+                IterMode::Ref => ("DEFINE_ITER", "DEFINE_ITER_NEXT_BACK"),
+                IterMode::Mut => ("DEFINE_ITER_MUT", "DEFINE_ITER_NEXT_BACK"),
+                IterMode::Drain => ("DEFINE_ITER_DRAIN", "DEFINE_ITER_DRAIN_BACK"),
+                IterMode::Copy => ("DEFINE_ITER_COPY", "DEFINE_ITER_COPY_BACK"),
+            };
+            // the parentheses keep commas in the range expression together
             lines.push(format!(
-                "DEFINE_ITER({}, {}, {})",
+                "{}({}, {}, ({}), {}, {})",
+                macro_name,
                 class_name,
                 iter.rust_name,
-                iter.yield_ty.get_flat_name()
+                iter.range.expr,
+                iter.range.begin,
+                iter.range.end
             ));
+            if iter.double_ended {
+                lines.push(format!("{}({}, {})", back_macro, class_name, iter.rust_name));
+            }
         }
         MethodDef::Method(func) => {
             let rust_name = &func.rust_name;
@@ -152,6 +387,12 @@ fn generate_method_shim(class_name: &str, method: &MethodDef, lines: &mut Vec<St
                         ));
                     }
                 }
+                MethodKind::Consuming => {
+                    lines.push(format!(
+                        "DEFINE_METHOD_CONSUME({}, {}, {})",
+                        class_name, rust_name, cpp_name
+                    ));
+                }
                 MethodKind::Mutable => {
                     let macro_name = if cpp_name == "operator()" {
                         "DEFINE_OP_CALL"
@@ -171,16 +412,39 @@ fn generate_method_shim(class_name: &str, method: &MethodDef, lines: &mut Vec<St
             }
         }
         MethodDef::Ctor(ctor) => {
-            if !ctor.is_user_defined {
+            if ctor.is_user_defined {
+                if ctor.needs_factory_shim(is_exposer) {
+                    // a protected factory is a static member reached through the Exposer
+                    let factory = if ctor.is_protected {
+                        format!("{}::{}", class_name, ctor.cpp_name)
+                    } else {
+                        ctor.cpp_name.to_string()
+                    };
+                    let macro_name = if ctor.on_failure == CtorFailure::Result {
+                        "DEFINE_CHECKED_FACTORY"
+                    } else {
+                        "DEFINE_EXPOSER_FACTORY"
+                    };
+                    lines.push(format!(
+                        "{}({}, {}, {})",
+                        macro_name, class_name, ctor.rust_name, factory
+                    ));
+                }
+            } else if ctor.is_protected {
+                lines.push(format!(
+                    "DEFINE_PROTECTED_CTOR({}, {})",
+                    class_name, ctor.rust_name
+                ));
+            } else {
                 lines.push(format!("DEFINE_CTOR({}, {})", class_name, ctor.rust_name));
             }
         }
     }
 }
 
-fn generate_vec_shims(vec_defs: &HashSet<TypeKind>, lines: &mut Vec<String>) {
-    let mut sorted_defs: Vec<&TypeKind> = vec_defs.iter().collect();
-    sorted_defs.sort_by(|a, b| a.get_flat_name().cmp(&b.get_flat_name()));
+fn generate_vec_shims(bind_context: &BindContext, lines: &mut Vec<String>) {
+    let mut sorted_defs: Vec<&TypeKind> = bind_context.vec_defs.iter().collect();
+    sorted_defs.sort_by_key(|a| a.get_flat_name());
 
     for def in sorted_defs {
         if let TypeKind::Vector { inner, is_ptr } = def {
@@ -196,16 +460,26 @@ fn generate_vec_shims(vec_defs: &HashSet<TypeKind>, lines: &mut Vec<String>) {
                 lines.push(format!("using {} = std::vector<{}>;", alias, elem_name));
             }
 
-            lines.push(format!("DEFINE_VEC_OPS({}, {})", alias, elem_name));
+            if inner.is_bool() {
+                lines.push(format!("DEFINE_VEC_BOOL_OPS({})", alias));
+            } else {
+                lines.push(format!("DEFINE_VEC_OPS({}, {})", alias, elem_name));
+            }
+            if bind_context.is_cloneable(def) {
+                lines.push(format!("DEFINE_CLONE({})", alias));
+            }
+            if bind_context.slice_vec_defs.contains(def) {
+                lines.push(format!("DEFINE_VEC_FROM_REFS({})", alias));
+            }
 
             lines.push("".to_string());
         }
     }
 }
 
-fn generate_map_shims(map_defs: &HashSet<TypeKind>, lines: &mut Vec<String>) {
-    let mut sorted_defs: Vec<&TypeKind> = map_defs.iter().collect();
-    sorted_defs.sort_by(|a, b| a.get_flat_name().cmp(&b.get_flat_name()));
+fn generate_map_shims(bind_context: &BindContext, lines: &mut Vec<String>) {
+    let mut sorted_defs: Vec<&TypeKind> = bind_context.map_defs.iter().collect();
+    sorted_defs.sort_by_key(|a| a.get_flat_name());
 
     for def in sorted_defs {
         if let TypeKind::Map {
@@ -231,6 +505,9 @@ fn generate_map_shims(map_defs: &HashSet<TypeKind>, lines: &mut Vec<String>) {
             }
 
             lines.push(format!("DEFINE_MAP_OPS({})", alias));
+            if bind_context.is_cloneable(def) {
+                lines.push(format!("DEFINE_CLONE({})", alias));
+            }
             lines.push("".to_string());
         }
     }
@@ -1,7 +1,6 @@
 use crate::ast::*;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use std::collections::HashSet;
 use syn::Ident;
 
 fn convert_args(args: &[Arg]) -> Vec<TokenStream> {
@@ -53,23 +52,21 @@ fn get_method_names(
 
 impl CtorDef {
     fn generate_ffi(&self, class_name: &Ident, needs_exposer: bool) -> TokenStream {
-        let (ffi_rust, ffi_cpp) = if self.is_user_defined {
-            if needs_exposer {
-                panic!("Custom factories not supported with Exposer");
-            }
-            (
-                format_ident!("{}_{}", class_name, self.rust_name),
-                self.cpp_name.clone(),
-            )
-        } else {
-            get_method_names(class_name, &self.rust_name, needs_exposer, true)
-        };
+        let (ffi_rust, mut ffi_cpp) =
+            get_method_names(class_name, &self.rust_name, needs_exposer, true);
+        if self.is_user_defined && !self.needs_factory_shim(needs_exposer) {
+            ffi_cpp = self.cpp_name.clone();
+        }
 
         let args = convert_args(&self.args);
+        let ret = match self.on_failure {
+            CtorFailure::Abort => quote! { UniquePtr<#class_name> },
+            CtorFailure::Result | CtorFailure::Option => quote! { Result<UniquePtr<#class_name>> },
+        };
 
         quote! {
             #[rust_name = #ffi_rust]
-            fn #ffi_cpp(#(#args),*) -> UniquePtr<#class_name>;
+            fn #ffi_cpp(#(#args),*) -> #ret;
         }
     }
 }
@@ -82,6 +79,25 @@ impl FnDef {
         let args = convert_args(&self.args);
         let ret = convert_ret(&self.ret_ty);
 
+        let borrows_self = self.kind != MethodKind::Static
+            && self.ret_ty.as_ref().is_some_and(|r| r.returns_reference());
+        if borrows_self {
+            let ret_ty = self
+                .ret_ty
+                .as_ref()
+                .unwrap()
+                .to_ffi_ret_type_with_lifetime(&quote! { 'a });
+            let self_arg = if self.kind == MethodKind::Const {
+                quote! { &'a #class_name }
+            } else {
+                quote! { Pin<&'a mut #class_name> }
+            };
+            return quote! {
+                #[rust_name = #ffi_rust]
+                fn #ffi_cpp<'a>(obj: #self_arg, #(#args),*) -> #ret_ty;
+            };
+        }
+
         match self.kind {
             MethodKind::Static => quote! {
                 #[rust_name = #ffi_rust]
@@ -95,36 +111,65 @@ impl FnDef {
                 #[rust_name = #ffi_rust]
                 fn #ffi_cpp(obj: Pin<&mut #class_name>, #(#args),*) #ret;
             },
+            MethodKind::Consuming => quote! {
+                #[rust_name = #ffi_rust]
+                fn #ffi_cpp(obj: UniquePtr<#class_name>, #(#args),*) #ret;
+            },
         }
     }
 }
 
 impl IterDef {
-    fn generate_ffi(&self, class_name: &Ident, needs_exposer: bool) -> TokenStream {
-        if needs_exposer {
-            panic!("Exposer pattern is not supported for iterators");
-        }
-
+    fn generate_ffi(&self, class_name: &Ident, cxx_class_name: &Ident) -> TokenStream {
         let iternames = IterNames::new(class_name, &self.rust_name);
+        let cxx_names = IterNames::new(cxx_class_name, &self.rust_name);
         let ctx_name = &iternames.ctx_name;
+        let cxx_ctx_name = cxx_names.ctx_name.to_string();
         let new_fn = &iternames.new_fn;
-        let next_fn = &iternames.next_fn;
-        let yield_ffi_ty = self.yield_ty.to_ffi_type_name_only();
+        let cxx_new_fn = &cxx_names.new_fn;
+        let done_fn = &iternames.done_fn;
+        let cxx_done_fn = &cxx_names.done_fn;
+
+        let self_arg = match self.mode {
+            IterMode::Ref | IterMode::Copy => quote! { &#class_name },
+            IterMode::Mut | IterMode::Drain => quote! { Pin<&mut #class_name> },
+        };
 
-        let self_arg = if self.is_iter_mut {
-            quote! { Pin<&mut #class_name> }
+        let next_decl = |name: &Ident, cxx_name: &Ident| match self.mode {
+            IterMode::Ref | IterMode::Mut => {
+                let ret_ty = self.yield_ty.to_ffi_ret_type_with_lifetime(&quote! { 'a });
+                quote! {
+                    #[rust_name = #name]
+                    fn #cxx_name<'a>(ctx: Pin<&'a mut #ctx_name>) -> #ret_ty;
+                }
+            }
+            IterMode::Drain | IterMode::Copy => {
+                let ret_ty = self.yield_ty.to_ffi_type(true);
+                quote! {
+                    #[rust_name = #name]
+                    fn #cxx_name(ctx: Pin<&mut #ctx_name>) -> #ret_ty;
+                }
+            }
+        };
+        let next = next_decl(&iternames.next_fn, &cxx_names.next_fn);
+        let next_back = if self.double_ended {
+            next_decl(&iternames.next_back_fn, &cxx_names.next_back_fn)
         } else {
-            quote! { &#class_name }
+            quote! {}
         };
 
         quote! {
+            #[cxx_name = #cxx_ctx_name]
             type #ctx_name;
 
             #[rust_name = #new_fn]
-            fn #new_fn(obj: #self_arg) -> UniquePtr<#ctx_name>;
+            fn #cxx_new_fn(obj: #self_arg) -> UniquePtr<#ctx_name>;
+
+            #[rust_name = #done_fn]
+            fn #cxx_done_fn(ctx: &#ctx_name) -> bool;
 
-            #[rust_name = #next_fn]
-            fn #next_fn(ctx: Pin<&mut #ctx_name>) -> UniquePtr<#yield_ffi_ty>;
+            #next
+            #next_back
         }
     }
 }
@@ -135,7 +180,7 @@ pub fn generate_ffi_method(class: &ClassModel, method: &MethodDef) -> TokenStrea
 
     match method {
         MethodDef::Ctor(ctor) => ctor.generate_ffi(class_name, needs_exposer),
-        MethodDef::Iter(iter) => iter.generate_ffi(class_name, needs_exposer),
+        MethodDef::Iter(iter) => iter.generate_ffi(class_name, &class.get_cxx_name()),
         MethodDef::Method(func) => func.generate_ffi(class_name, needs_exposer),
     }
 }
@@ -180,7 +225,7 @@ fn generate_ffi_field(class: &ClassModel, field: &FieldDef) -> TokenStream {
     };
 
     match &field.ty {
-        TypeKind::Primitive(_) | TypeKind::String => {
+        TypeKind::Primitive(_) | TypeKind::String | TypeKind::Enum(_) => {
             let ret_ty = field.ty.to_ffi_type(true);
             let get = make_getter(ret_ty, true);
 
@@ -194,13 +239,17 @@ fn generate_ffi_field(class: &ClassModel, field: &FieldDef) -> TokenStream {
         }
 
         TypeKind::Object(_) | TypeKind::Vector { .. } | TypeKind::Map { .. } => {
-            if field.is_readonly {
+            let get = if field.has_mut_getter() {
+                let ret_kind = TypeKind::new_mut_ref(field.ty.clone());
+                make_getter(ret_kind.to_ffi_type(true), false)
+            } else {
                 let ret_kind = TypeKind::new_const_ref(field.ty.clone());
                 make_getter(ret_kind.to_ffi_type(true), true)
-            } else {
-                let ret_kind = TypeKind::new_mut_ref(field.ty.clone());
-                let get = make_getter(ret_kind.to_ffi_type(true), false);
+            };
 
+            if field.is_readonly {
+                get
+            } else {
                 let arg_kind = TypeKind::new_unique_ptr(field.ty.clone());
 
                 let set = make_setter(arg_kind.to_ffi_type(false));
@@ -236,7 +285,29 @@ fn generate_ffi_field(class: &ClassModel, field: &FieldDef) -> TokenStream {
                     false => inner_ty.to_ffi_type(false),
                 };
                 let set = make_setter(arg_ty_tokens);
-                quote! { #get #set }
+                let cxx_clear = format_ident!("{}_clear_{}", class.get_cxx_name(), field_name);
+                let rust_clear = field.get_ffi_clear_name(class_name);
+                quote! {
+                    #get
+                    #set
+                    #[rust_name = #rust_clear]
+                    fn #cxx_clear(obj: Pin<&mut #class_name>);
+                }
+            }
+        }
+
+        TypeKind::Reference { inner, is_mut } if matches!(**inner, TypeKind::Slice(_)) => {
+            let get = make_getter(TypeKind::new_const_ref(*inner.clone()).to_ffi_type(true), true);
+            if !*is_mut {
+                return get;
+            }
+            let cxx_get_mut = format_ident!("{}_mut", cxx_get);
+            let rust_get_mut = format_ident!("{}_mut", rust_get);
+            let ret_ty = field.ty.to_ffi_type(true);
+            quote! {
+                #get
+                #[rust_name = #rust_get_mut]
+                fn #cxx_get_mut(obj: Pin<&mut #class_name>) -> #ret_ty;
             }
         }
 
@@ -265,13 +336,164 @@ pub fn generate_ffi_block(class: &ClassModel) -> TokenStream {
     for method in &class.methods {
         items.push(generate_ffi_method(class, method));
     }
+
+    if let Some(contiguous) = &class.contiguous {
+        let elem = contiguous.elem.to_ffi_type_name_only();
+        let cxx_name = class.get_cxx_name();
+        let (rust_slice, cxx_slice) = (
+            format_ident!("{}_as_slice", class_name),
+            format_ident!("{}_as_slice", cxx_name),
+        );
+        let (rust_mut_slice, cxx_mut_slice) = (
+            format_ident!("{}_as_mut_slice", class_name),
+            format_ident!("{}_as_mut_slice", cxx_name),
+        );
+        items.push(quote! {
+            #[rust_name = #rust_slice]
+            fn #cxx_slice(obj: &#class_name) -> &[#elem];
+            #[rust_name = #rust_mut_slice]
+            fn #cxx_mut_slice(obj: Pin<&mut #class_name>) -> &mut [#elem];
+        });
+        if contiguous.resize {
+            let rust_resize = format_ident!("{}_slice_resize", class_name);
+            let cxx_resize = format_ident!("{}_slice_resize", cxx_name);
+            items.push(quote! {
+                #[rust_name = #rust_resize]
+                fn #cxx_resize(obj: Pin<&mut #class_name>, len: usize);
+            });
+        }
+    }
+
+    if class.has_derive("Clone") {
+        let rust_clone = get_clone_fn_name(&TypeKind::Object(class_name.to_string()));
+        let cxx_clone = format_ident!("{}_clone", class.get_cxx_name());
+        items.push(quote! {
+            #[rust_name = #rust_clone]
+            fn #cxx_clone(obj: &#class_name) -> UniquePtr<#class_name>;
+        });
+    }
+
+    if class.has_derive("Init") {
+        let rust_fn = get_from_init_fn_name(class_name);
+        let cxx_fn = get_from_init_fn_name(&class.get_cxx_name());
+        let params = class.fields.iter().flat_map(|field| {
+            let name = &field.name;
+            match field.init_arg().unwrap() {
+                InitArg::Value => {
+                    let t = field.ty.to_ffi_type(false);
+                    vec![quote! { #name: #t }]
+                }
+                InitArg::Optional => {
+                    let present = format_ident!("{}_present", name);
+                    let TypeKind::Option(inner) = &field.ty else {
+                        unreachable!()
+                    };
+                    let t = inner.to_ffi_type(false);
+                    vec![quote! { #present: bool }, quote! { #name: #t }]
+                }
+                InitArg::Object => {
+                    let obj = match &field.ty {
+                        TypeKind::Option(inner) => inner.as_ref(),
+                        ty => ty,
+                    };
+                    let t = obj.to_ffi_type(false);
+                    vec![quote! { #name: #t }]
+                }
+            }
+        });
+        items.push(quote! {
+            #[rust_name = #rust_fn]
+            fn #cxx_fn(#(#params),*) -> UniquePtr<#class_name>;
+        });
+    }
+
+    if class.has_derive("Mirror") {
+        let data_name = get_mirror_data_name(class_name);
+        let (snapshot_fn, apply_fn) = get_mirror_fn_names(class_name);
+        items.push(quote! {
+            fn #snapshot_fn(obj: &#class_name) -> #data_name;
+            fn #apply_fn(obj: Pin<&mut #class_name>, data: &#data_name);
+        });
+    }
+
+    if class.is_display {
+        let rust_fn = get_to_string_fn_name(class_name);
+        let cxx_fn = get_to_string_fn_name(&class.get_cxx_name());
+        items.push(quote! {
+            #[rust_name = #rust_fn]
+            fn #cxx_fn(obj: &#class_name) -> String;
+        });
+    }
+
+    if class.is_from_str {
+        let rust_fn = get_from_str_fn_name(class_name);
+        let cxx_fn = get_from_str_fn_name(&class.get_cxx_name());
+        items.push(quote! {
+            #[rust_name = #rust_fn]
+            fn #cxx_fn(s: &str) -> Result<UniquePtr<#class_name>>;
+        });
+    }
     quote! { #(#items)* }
 }
 
-pub fn generate_vec_ffi(vec_defs: &HashSet<TypeKind>) -> TokenStream {
+/// The `{Class}Data` struct of `#[derive(Mirror)]`, shared with C++ by value.
+pub fn generate_mirror_struct(class: &ClassModel) -> TokenStream {
+    if !class.has_derive("Mirror") {
+        return quote! {};
+    }
+    let data_name = get_mirror_data_name(&class.name);
+    let fields = class.mirror_fields.iter().map(|mirror| {
+        let name = &mirror.field.name;
+        let ty = match &mirror.nested {
+            Some(nested) => {
+                let t = get_mirror_data_name(nested);
+                quote! { #t }
+            }
+            None => mirror.field.ty.to_ffi_type(true),
+        };
+        quote! { #name: #ty }
+    });
+    quote! {
+        #[derive(Clone, Debug, Default, PartialEq)]
+        struct #data_name {
+            #(#fields),*
+        }
+    }
+}
+
+/// A declared enum becomes a shared cxx enum backed by the existing C++ one:
+/// the definition goes next to the mirror structs, `type Name;` into the
+/// `extern "C++"` block.
+pub fn generate_enum_ffi(def: &EnumDef) -> (TokenStream, TokenStream) {
+    let name = &def.name;
+    let repr = &def.repr;
+    let variants = &def.variants;
+    let shared = quote! {
+        #[repr(#repr)]
+        #[derive(Debug, Hash, PartialOrd, Ord)]
+        enum #name {
+            #(#variants),*
+        }
+    };
+    (shared, quote! { type #name; })
+}
+
+fn generate_container_clone_ffi(bind_context: &BindContext, def: &TypeKind) -> TokenStream {
+    if !bind_context.is_cloneable(def) {
+        return quote! {};
+    }
+    let ffi_type_name = def.to_ffi_type_name_only();
+    let clone_fn = get_clone_fn_name(def);
+    quote! {
+        #[rust_name = #clone_fn]
+        fn #clone_fn(obj: &#ffi_type_name) -> UniquePtr<#ffi_type_name>;
+    }
+}
+
+pub fn generate_vec_ffi(bind_context: &BindContext) -> TokenStream {
     let mut items = Vec::new();
 
-    let mut sorted_defs: Vec<&TypeKind> = vec_defs.iter().collect();
+    let mut sorted_defs: Vec<&TypeKind> = bind_context.vec_defs.iter().collect();
     sorted_defs.sort_by(|a, b| {
         let name_a = a.get_flat_name();
         let name_b = b.get_flat_name();
@@ -300,6 +522,16 @@ pub fn generate_vec_ffi(vec_defs: &HashSet<TypeKind>) -> TokenStream {
                 #[rust_name = #len_fn]
                 fn #len_fn(obj: &#ffi_type_name) -> usize;
             });
+            items.push(generate_container_clone_ffi(bind_context, def));
+
+            if bind_context.slice_vec_defs.contains(def) {
+                let from_refs_fn = get_from_refs_fn_name(def);
+                let t = elem_ty.to_ffi_type_name_only();
+                items.push(quote! {
+                    #[rust_name = #from_refs_fn]
+                    fn #from_refs_fn(items: &[*const #t]) -> UniquePtr<#ffi_type_name>;
+                });
+            }
 
             let get_ret_ty = if let TypeKind::String = **elem_ty {
                 quote! { Result<String> }
@@ -327,7 +559,9 @@ pub fn generate_vec_ffi(vec_defs: &HashSet<TypeKind>) -> TokenStream {
                 fn #push_fn(obj: Pin<&mut #ffi_type_name>, val: #push_arg_ty);
             });
 
-            if !matches!(**elem_ty, TypeKind::String) {
+            let is_bool = elem_ty.is_bool();
+
+            if !matches!(**elem_ty, TypeKind::String) && !is_bool {
                 let t = elem_ty.to_ffi_type_name_only();
                 let ret_ty = quote! { Result<Pin<&mut #t>> };
 
@@ -335,14 +569,47 @@ pub fn generate_vec_ffi(vec_defs: &HashSet<TypeKind>) -> TokenStream {
                     #[rust_name = #get_mut_fn]
                     fn #get_mut_fn(obj: Pin<&mut #ffi_type_name>, index: usize) -> #ret_ty;
                 });
-            } else {
+            }
+            if !elem_ty.is_scalar() || is_bool {
                 items.push(quote! {
                     #[rust_name = #set_fn]
-                    fn #set_fn(obj: Pin<&mut #ffi_type_name>, index: usize, val: &str);
+                    fn #set_fn(obj: Pin<&mut #ffi_type_name>, index: usize, val: #push_arg_ty);
                 });
             }
 
-            if let TypeKind::Primitive(_) = **elem_ty {
+            items.push(generate_vec_modify_ffi(def, &push_arg_ty));
+
+            let extend_fn = format_ident!("{}_extend_from_slice", ffi_type_str);
+            match &**elem_ty {
+                TypeKind::Primitive(_) if is_bool => {
+                    let to_vec_fn = format_ident!("{}_to_vec", ffi_type_str);
+                    items.push(quote! {
+                        #[rust_name = #extend_fn]
+                        fn #extend_fn(obj: Pin<&mut #ffi_type_name>, vals: &[bool]);
+                        #[rust_name = #to_vec_fn]
+                        fn #to_vec_fn(obj: &#ffi_type_name) -> Vec<bool>;
+                    });
+                }
+                TypeKind::Primitive(_) | TypeKind::Enum(_) => {
+                    let t = elem_ty.to_ffi_type_name_only();
+                    items.push(quote! {
+                        #[rust_name = #extend_fn]
+                        fn #extend_fn(obj: Pin<&mut #ffi_type_name>, vals: &[#t]);
+                    });
+                }
+                TypeKind::String => {
+                    let to_vec_fn = format_ident!("{}_to_vec", ffi_type_str);
+                    items.push(quote! {
+                        #[rust_name = #extend_fn]
+                        fn #extend_fn(obj: Pin<&mut #ffi_type_name>, vals: &[&str]);
+                        #[rust_name = #to_vec_fn]
+                        fn #to_vec_fn(obj: &#ffi_type_name) -> Vec<String>;
+                    });
+                }
+                _ => {}
+            }
+
+            if elem_ty.is_scalar() && !is_bool {
                 let slice_fn = format_ident!("{}_as_slice", ffi_type_str);
                 let mut_slice_fn = format_ident!("{}_as_mut_slice", ffi_type_str);
                 let t = elem_ty.to_ffi_type_name_only();
@@ -359,13 +626,54 @@ pub fn generate_vec_ffi(vec_defs: &HashSet<TypeKind>) -> TokenStream {
     quote! { #(#items)* }
 }
 
-pub fn generate_map_ffi(
-    map_defs: &HashSet<TypeKind>,
-) -> TokenStream {
+fn generate_vec_modify_ffi(def: &TypeKind, push_arg_ty: &TokenStream) -> TokenStream {
+    let TypeKind::Vector { inner, .. } = def else {
+        unreachable!()
+    };
+    let ffi_type_name = def.to_ffi_type_name_only();
+    let ffi_type_str = def.get_flat_name();
+    let elem_ret_ty = inner.to_ffi_type(true);
+
+    let clear_fn = format_ident!("{}_clear", ffi_type_str);
+    let truncate_fn = format_ident!("{}_truncate", ffi_type_str);
+    let reserve_fn = format_ident!("{}_reserve", ffi_type_str);
+    let capacity_fn = format_ident!("{}_capacity", ffi_type_str);
+    let swap_fn = format_ident!("{}_swap", ffi_type_str);
+    let pop_fn = format_ident!("{}_pop", ffi_type_str);
+    let remove_fn = format_ident!("{}_remove", ffi_type_str);
+    let swap_remove_fn = format_ident!("{}_swap_remove", ffi_type_str);
+    let insert_fn = format_ident!("{}_insert", ffi_type_str);
+    let retain_fn = format_ident!("{}_retain_mask", ffi_type_str);
+
+    quote! {
+        #[rust_name = #clear_fn]
+        fn #clear_fn(obj: Pin<&mut #ffi_type_name>);
+        #[rust_name = #truncate_fn]
+        fn #truncate_fn(obj: Pin<&mut #ffi_type_name>, len: usize);
+        #[rust_name = #reserve_fn]
+        fn #reserve_fn(obj: Pin<&mut #ffi_type_name>, additional: usize);
+        #[rust_name = #capacity_fn]
+        fn #capacity_fn(obj: &#ffi_type_name) -> usize;
+        #[rust_name = #swap_fn]
+        fn #swap_fn(obj: Pin<&mut #ffi_type_name>, a: usize, b: usize);
+        #[rust_name = #pop_fn]
+        fn #pop_fn(obj: Pin<&mut #ffi_type_name>) -> #elem_ret_ty;
+        #[rust_name = #remove_fn]
+        fn #remove_fn(obj: Pin<&mut #ffi_type_name>, index: usize) -> #elem_ret_ty;
+        #[rust_name = #swap_remove_fn]
+        fn #swap_remove_fn(obj: Pin<&mut #ffi_type_name>, index: usize) -> #elem_ret_ty;
+        #[rust_name = #insert_fn]
+        fn #insert_fn(obj: Pin<&mut #ffi_type_name>, index: usize, val: #push_arg_ty);
+        #[rust_name = #retain_fn]
+        fn #retain_fn(obj: Pin<&mut #ffi_type_name>, keep: &[bool]);
+    }
+}
+
+pub fn generate_map_ffi(bind_context: &BindContext) -> TokenStream {
     let mut items = Vec::new();
 
-    let mut sorted_defs: Vec<&TypeKind> = map_defs.iter().collect();
-    sorted_defs.sort_by(|a, b| a.get_flat_name().cmp(&b.get_flat_name()));
+    let mut sorted_defs: Vec<&TypeKind> = bind_context.map_defs.iter().collect();
+    sorted_defs.sort_by_key(|a| a.get_flat_name());
 
     for def in sorted_defs {
         if let TypeKind::Map {
@@ -378,7 +686,11 @@ pub fn generate_map_ffi(
             let ffi_type_str = def.get_flat_name();
             let len_fn = format_ident!("{}_len", ffi_type_str);
             let get_fn = format_ident!("{}_get", ffi_type_str);
-            let _insert_fn = format_ident!("{}_insert", ffi_type_str);
+            let insert_fn = format_ident!("{}_insert", ffi_type_str);
+            let remove_fn = format_ident!("{}_remove", ffi_type_str);
+            let contains_fn = format_ident!("{}_contains_key", ffi_type_str);
+            let clear_fn = format_ident!("{}_clear", ffi_type_str);
+            let get_mut_fn = format_ident!("{}_get_mut", ffi_type_str);
             let new_fn = format_ident!("make_{}_new", ffi_type_str);
 
             items.push(quote! {
@@ -388,13 +700,16 @@ pub fn generate_map_ffi(
                 #[rust_name = #len_fn]
                 fn #len_fn(obj: &#ffi_type_name) -> usize;
             });
-
-            let key_arg_ty = if let TypeKind::String = **key {
-                quote! { &str }
-            } else if let TypeKind::Primitive(_) = **key {
-                key.to_ffi_type_name_only()
-            } else {
-                panic!("Now only primitive and string keys.");
+            items.push(generate_container_clone_ffi(bind_context, def));
+
+            let key_arg_ty = match &**key {
+                TypeKind::String => quote! { &str },
+                TypeKind::Primitive(_) | TypeKind::Enum(_) => key.to_ffi_type_name_only(),
+                TypeKind::Object(_) => {
+                    let t = key.to_ffi_type_name_only();
+                    quote! { &#t }
+                }
+                _ => panic!("Map keys must be primitives, strings, enums or bound classes."),
             };
 
             let (val_ret_ty, lifetime) = if let TypeKind::String = **value {
@@ -418,21 +733,71 @@ pub fn generate_map_ffi(
                 fn #get_fn #lifetime (#self_arg, key: #key_arg_ty) -> Result<#val_ret_ty>;
             });
 
-            // key: String -> &str, Prim -> T
-            // val: String -> &str, Prim -> T, Obj -> UniquePtr
-            // let val_arg_ty = if let TypeKind::String = **value {
-            //     quote! { &str }
-            // } else if value.is_object_value() {
-            //     let t = value.to_ffi_type_name_only();
-            //     quote! { UniquePtr<#t> }
-            // } else {
-            //     value.to_ffi_type_name_only()
-            // };
-
-            // items.push(quote! {
-            //     #[rust_name = #insert_fn]
-            //     fn #insert_fn(obj: Pin<&mut #ffi_type_name>, key: #key_arg_ty, val: #val_arg_ty);
-            // });
+            let val_arg_ty = if let TypeKind::String = **value {
+                quote! { &str }
+            } else {
+                value.to_ffi_type(false)
+            };
+            let val_ret_ty = value.to_ffi_type(true);
+
+            // the replaced value comes back as a null pointer or through `old`
+            let insert_decl = if value.is_object_value() {
+                quote! {
+                    fn #insert_fn(
+                        obj: Pin<&mut #ffi_type_name>,
+                        key: #key_arg_ty,
+                        val: #val_arg_ty,
+                    ) -> #val_ret_ty;
+                }
+            } else {
+                quote! {
+                    fn #insert_fn(
+                        obj: Pin<&mut #ffi_type_name>,
+                        key: #key_arg_ty,
+                        val: #val_arg_ty,
+                        old: &mut #val_ret_ty,
+                    ) -> bool;
+                }
+            };
+            items.push(quote! {
+                #[rust_name = #insert_fn]
+                #insert_decl
+                #[rust_name = #remove_fn]
+                fn #remove_fn(obj: Pin<&mut #ffi_type_name>, key: #key_arg_ty) -> Result<#val_ret_ty>;
+                #[rust_name = #contains_fn]
+                fn #contains_fn(obj: &#ffi_type_name, key: #key_arg_ty) -> bool;
+                #[rust_name = #clear_fn]
+                fn #clear_fn(obj: Pin<&mut #ffi_type_name>);
+            });
+
+            if key.is_bulk_copyable() && value.is_bulk_copyable() {
+                let to_vecs_fn = format_ident!("{}_to_vecs", ffi_type_str);
+                let from_vecs_fn = format_ident!("make_{}_from_vecs", ffi_type_str);
+                let k = key.to_ffi_type(true);
+                let v = value.to_ffi_type(true);
+                items.push(quote! {
+                    #[rust_name = #to_vecs_fn]
+                    fn #to_vecs_fn(obj: &#ffi_type_name, keys: &mut Vec<#k>, vals: &mut Vec<#v>);
+                    #[rust_name = #from_vecs_fn]
+                    fn #from_vecs_fn(keys: &Vec<#k>, vals: &Vec<#v>) -> UniquePtr<#ffi_type_name>;
+                });
+            }
+
+            // primitives and strings are handed out in place for `get_mut` / `iter_mut`
+            let val_mut_ty = match **value {
+                TypeKind::Primitive(_) | TypeKind::Enum(_) => {
+                    let t = value.to_ffi_type_name_only();
+                    Some(quote! { &'a mut #t })
+                }
+                TypeKind::String => Some(quote! { Pin<&'a mut CxxString> }),
+                _ => None,
+            };
+            if let Some(val_mut_ty) = &val_mut_ty {
+                items.push(quote! {
+                    #[rust_name = #get_mut_fn]
+                    fn #get_mut_fn<'a>(obj: Pin<&'a mut #ffi_type_name>, key: #key_arg_ty) -> Result<#val_mut_ty>;
+                });
+            }
 
             let iter_ctx_name = format_ident!("{}_IterCtx", ffi_type_str);
             let iter_new_fn = format_ident!("{}_iter_new", ffi_type_str);
@@ -443,6 +808,9 @@ pub fn generate_map_ffi(
 
             let key_iter_ret = if let TypeKind::String = **key {
                 quote! { String }
+            } else if let TypeKind::Object(_) = **key {
+                let t = key.to_ffi_type_name_only();
+                quote! { &#t }
             } else {
                 key.to_ffi_type_name_only()
             };
@@ -470,6 +838,13 @@ pub fn generate_map_ffi(
                 #[rust_name = #iter_is_end_fn]
                 fn #iter_is_end_fn(ctx: Pin<&mut #iter_ctx_name>) -> bool;
             });
+            if let Some(val_mut_ty) = &val_mut_ty {
+                let iter_val_mut_fn = format_ident!("{}_iter_val_mut", ffi_type_str);
+                items.push(quote! {
+                    #[rust_name = #iter_val_mut_fn]
+                    fn #iter_val_mut_fn<'a>(ctx: Pin<&'a mut #iter_ctx_name>) -> #val_mut_ty;
+                });
+            }
         }
     }
     quote! { #(#items)* }
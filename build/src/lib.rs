@@ -68,15 +68,79 @@ fn collect_recursive(ty: &TypeKind, vecs: &mut HashSet<TypeKind>, maps: &mut Has
             collect_recursive(value, vecs, maps);
         }
 
+        TypeKind::Slice(inner) => {
+            if let TypeKind::Object(_) = &**inner {
+                vecs.insert(inner.slice_vec_type());
+            }
+            collect_recursive(inner, vecs, maps);
+        }
+
         TypeKind::Reference { inner, .. }
         | TypeKind::Option(inner)
         | TypeKind::Result(inner)
-        | TypeKind::UniquePtr(inner)
-        | TypeKind::Slice(inner) => {
+        | TypeKind::UniquePtr(inner) => {
             collect_recursive(inner, vecs, maps);
         }
 
-        TypeKind::Primitive(_) | TypeKind::String | TypeKind::Object(_) => {}
+        TypeKind::Primitive(_) | TypeKind::String | TypeKind::Object(_) | TypeKind::Enum(_) => {}
+    }
+}
+
+/// Types naming a declared enum are parsed as objects until every item is known.
+fn resolve_enum_types(models: &mut HashMap<String, ClassModel>, enums: &[EnumDef]) {
+    let names: HashSet<String> = enums.iter().map(|e| e.name.to_string()).collect();
+    if let Some(name) = names.iter().find(|name| models.contains_key(*name)) {
+        panic!("'{}' is declared both as a struct and as an enum", name);
+    }
+    if names.is_empty() {
+        return;
+    }
+
+    for model in models.values_mut() {
+        for field in &mut model.fields {
+            field.ty.resolve_enums(&names);
+        }
+        for method in &mut model.methods {
+            match method {
+                MethodDef::Method(f) => {
+                    for arg in &mut f.args {
+                        arg.ty.resolve_enums(&names);
+                    }
+                    if let Some(ret) = &mut f.ret_ty {
+                        ret.resolve_enums(&names);
+                    }
+                }
+                MethodDef::Ctor(c) => {
+                    for arg in &mut c.args {
+                        arg.ty.resolve_enums(&names);
+                    }
+                }
+                MethodDef::Iter(iter) => iter.yield_ty.resolve_enums(&names),
+            }
+        }
+    }
+}
+
+/// Whether an `#[iter]` item copies or borrows is only known once enums are.
+fn check_iter_items(models: &HashMap<String, ClassModel>) {
+    for model in models.values() {
+        for method in &model.methods {
+            let MethodDef::Iter(iter) = method else {
+                continue;
+            };
+            let error = match (&iter.yield_ty, iter.mode) {
+                (ty, IterMode::Copy) if !ty.is_scalar() && *ty != TypeKind::String => {
+                    "by-value object items move out of the container, use Item = &T or #[iter(drain)]"
+                }
+                (TypeKind::Reference { inner, .. }, IterMode::Ref | IterMode::Mut)
+                    if !inner.is_object_value() =>
+                {
+                    "enums are copied, use Item = T"
+                }
+                _ => continue,
+            };
+            panic!("#[iter] on {}::{}: {}", model.name, iter.rust_name, error);
+        }
     }
 }
 
@@ -87,12 +151,14 @@ fn inject_default_ctors(models: &mut HashMap<String, ClassModel>) {
             .iter()
             .any(|m| matches!(m, MethodDef::Ctor(_)));
 
-        if !has_ctor {
+        if !has_ctor && !model.no_default_ctor {
             let default_ctor = MethodDef::Ctor(CtorDef {
                 rust_name: format_ident!("new"),
                 args: vec![],
                 cpp_name: format_ident!("new"),
                 is_user_defined: false,
+                is_protected: false,
+                on_failure: CtorFailure::Abort,
             });
 
             model.methods.push(default_ctor);
@@ -100,10 +166,223 @@ fn inject_default_ctors(models: &mut HashMap<String, ClassModel>) {
     }
 }
 
+/// Fields and returned references of `#[contiguous]` classes are exposed as
+/// slices over their storage.
+fn rewrite_contiguous_types(models: &mut HashMap<String, ClassModel>) {
+    let elems: HashMap<String, TypeKind> = models
+        .iter()
+        .filter_map(|(name, m)| m.contiguous.as_ref().map(|c| (name.clone(), c.elem.clone())))
+        .collect();
+    if elems.is_empty() {
+        return;
+    }
+
+    let as_slice = |ty: &TypeKind, is_mut: bool| match ty {
+        TypeKind::Object(name) => elems
+            .get(name)
+            .map(|elem| TypeKind::new_ref(TypeKind::Slice(Box::new(elem.clone())), is_mut)),
+        _ => None,
+    };
+
+    for model in models.values_mut() {
+        for field in &mut model.fields {
+            if let Some(slice) = as_slice(&field.ty, field.has_mut_getter()) {
+                field.ty = slice;
+            }
+        }
+        for method in &mut model.methods {
+            if let MethodDef::Method(func) = method
+                && let Some(TypeKind::Reference { inner, is_mut }) = &func.ret_ty
+                && let Some(slice) = as_slice(inner, *is_mut)
+            {
+                func.ret_ty = Some(slice);
+            }
+        }
+    }
+}
+
+/// `&[T]` arguments of objects are copied into a temporary `std::vector<T>`
+/// by one shim per element type.
+fn collect_slice_vecs(models: &HashMap<String, ClassModel>) -> HashSet<TypeKind> {
+    let mut defs = HashSet::new();
+    for model in models.values() {
+        for method in &model.methods {
+            let args = match method {
+                MethodDef::Method(f) => &f.args,
+                MethodDef::Ctor(c) => &c.args,
+                MethodDef::Iter(_) => continue,
+            };
+            for arg in args {
+                if let TypeKind::Reference { inner, .. } = &arg.ty
+                    && let TypeKind::Slice(elem) = &**inner
+                    && let TypeKind::Object(_) = &**elem
+                {
+                    defs.insert(elem.slice_vec_type());
+                }
+            }
+        }
+    }
+    defs
+}
+
+/// `#[derive(Init)]` builds the object with aggregate initialization, which
+/// needs every bound field to be a public data member.
+fn check_init_fields(models: &HashMap<String, ClassModel>) {
+    for model in models.values().filter(|m| m.has_derive("Init")) {
+        for field in &model.fields {
+            if field.is_protected || field.accessors.is_some() {
+                panic!(
+                    "#[derive(Init)] on {}: '{}' is not a public data member",
+                    model.name, field.name
+                );
+            }
+            if field.init_arg().is_none() {
+                panic!(
+                    "#[derive(Init)] on {}: field '{}' has unsupported type {:?}",
+                    model.name, field.name, field.ty
+                );
+            }
+        }
+    }
+}
+
+/// A method with defaulted arguments also gets `{name}_with(..)`, which may
+/// not shadow a method the DSL binds itself.
+fn check_default_args(models: &HashMap<String, ClassModel>) {
+    for model in models.values() {
+        let names: HashSet<String> = model
+            .methods
+            .iter()
+            .map(|m| m.rust_name().to_string())
+            .collect();
+        for method in model.methods.iter().filter(|m| m.has_default_args()) {
+            let with_name = format!("{}_with", method.rust_name());
+            if names.contains(&with_name) {
+                panic!(
+                    "{}::{} has default arguments, its generated '{}' collides with a bound method",
+                    model.name,
+                    method.rust_name(),
+                    with_name
+                );
+            }
+        }
+    }
+}
+
+/// Structs generated for a class sit next to the bound types in the same
+/// module, so none of them may reuse a name.
+fn check_generated_names(models: &HashMap<String, ClassModel>, enums: &[EnumDef]) {
+    let mut taken: HashMap<String, String> = models
+        .keys()
+        .map(|name| (name.clone(), "a bound class".to_string()))
+        .chain(enums.iter().map(|e| (e.name.to_string(), "a bound enum".to_string())))
+        .collect();
+
+    let mut names: Vec<&String> = models.keys().collect();
+    names.sort();
+    for name in names {
+        let model = &models[name];
+        let mut generated = Vec::new();
+        for method in model.methods.iter().filter(|m| m.has_default_args()) {
+            generated.push((
+                get_default_args_struct_name(&model.name, method.rust_name()),
+                format!("the default arguments of {}::{}", model.name, method.rust_name()),
+            ));
+        }
+        if model.has_derive("Init") {
+            generated.push((
+                get_init_struct_name(&model.name),
+                format!("#[derive(Init)] on {}", model.name),
+            ));
+        }
+        if model.has_derive("Mirror") {
+            generated.push((
+                get_mirror_data_name(&model.name),
+                format!("#[derive(Mirror)] on {}", model.name),
+            ));
+        }
+
+        for (struct_name, origin) in generated {
+            if let Some(owner) = taken.get(&struct_name.to_string()) {
+                panic!("'{}', generated for {}, collides with {}", struct_name, origin, owner);
+            }
+            taken.insert(struct_name.to_string(), origin);
+        }
+    }
+}
+
+/// `#[derive(Serialize)]` writes every public field through its getter and
+/// `#[derive(Deserialize)]` sets every writable one on a `new()` object.
+fn check_serde_fields(context: &BindContext) {
+    for model in context.models.values() {
+        let public = model.fields.iter().filter(|f| !f.is_protected);
+        if model.has_derive("Serialize") {
+            for field in public.clone() {
+                if !context.is_serializable(&field.ty) {
+                    panic!(
+                        "#[derive(Serialize)] on {}: field '{}' of type {:?} is not serializable",
+                        model.name, field.name, field.ty
+                    );
+                }
+            }
+        }
+        if model.has_derive("Deserialize") {
+            if !model.has_default_new() {
+                panic!(
+                    "#[derive(Deserialize)] on {}: needs a `fn new() -> Self` constructor",
+                    model.name
+                );
+            }
+            for field in public.filter(|f| f.has_setter()) {
+                if !context.is_deserializable(&field.ty) {
+                    panic!(
+                        "#[derive(Deserialize)] on {}: field '{}' of type {:?} is not deserializable",
+                        model.name, field.name, field.ty
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// `#[derive(Mirror)]` copies primitives, strings and objects that are
+/// themselves mirrored; protected members and other types are left out.
+fn collect_mirror_fields(models: &mut HashMap<String, ClassModel>) {
+    let mirrored: HashSet<String> = models
+        .iter()
+        .filter(|(_, m)| m.has_derive("Mirror"))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    for model in models.values_mut().filter(|m| m.has_derive("Mirror")) {
+        model.mirror_fields = model
+            .fields
+            .iter()
+            .filter(|field| !field.is_protected)
+            .filter_map(|field| {
+                let nested = match &field.ty {
+                    TypeKind::Primitive(_) | TypeKind::String => None,
+                    TypeKind::Object(name)
+                        if mirrored.contains(name) && field.accessors.is_none() =>
+                    {
+                        Some(format_ident!("{}", name))
+                    }
+                    _ => return None,
+                };
+                Some(MirrorField {
+                    field: field.clone(),
+                    nested,
+                })
+            })
+            .collect();
+    }
+}
+
 pub fn preprocess(input: &BindInput) -> BindContext {
     let mut includes = Vec::new();
     let mut models = HashMap::new();
     let mut class_names_order = Vec::new();
+    let mut enums = Vec::new();
 
     for item in &input.items {
         match item {
@@ -115,17 +394,26 @@ pub fn preprocess(input: &BindInput) -> BindContext {
 
                 model.needs_exposer = def.fields.iter().any(|f| f.is_protected);
                 model.fields = def.fields.clone();
+                model.derives = def.derives.clone();
+                model.debug_protected = def.debug_protected;
+                model.is_display = def.is_display;
+                model.is_from_str = def.is_from_str;
+                model.contiguous = def.contiguous.clone();
+                model.no_default_ctor = def.no_default_ctor;
+                if model.no_default_ctor && model.is_from_str {
+                    panic!(
+                        "#[from_str] on '{}' parses into a default-constructed object",
+                        name_str
+                    );
+                }
                 models.insert(name_str.clone(), model);
                 class_names_order.push(name_str);
             }
+            BindItem::Enum(def) => enums.push(def.clone()),
             BindItem::Impl(def) => {
                 let target = def.target.to_string();
                 if let Some(model) = models.get_mut(&target) {
-                    if def
-                        .methods
-                        .iter()
-                        .any(|m| matches!(m, MethodDef::Method(f) if f.is_protected))
-                    {
+                    if def.methods.iter().any(MethodDef::is_protected) {
                         model.needs_exposer = true;
                     }
                     model.methods.extend(def.methods.clone());
@@ -137,16 +425,28 @@ pub fn preprocess(input: &BindInput) -> BindContext {
     }
 
     inject_default_ctors(&mut models);
+    resolve_enum_types(&mut models, &enums);
+    check_iter_items(&models);
+    rewrite_contiguous_types(&mut models);
+    check_init_fields(&models);
+    check_default_args(&models);
+    check_generated_names(&models, &enums);
+    collect_mirror_fields(&mut models);
 
     let (vec_defs, map_defs) = collect_containers(&models);
+    let slice_vec_defs = collect_slice_vecs(&models);
 
-    BindContext {
+    let context = BindContext {
         includes,
         models,
         class_names_order,
+        enums,
         vec_defs,
+        slice_vec_defs,
         map_defs,
-    }
+    };
+    check_serde_fields(&context);
+    context
 }
 
 pub fn bridge(rust_source_file: impl AsRef<Path>) -> cc::Build {
@@ -165,13 +465,13 @@ pub fn bridge(rust_source_file: impl AsRef<Path>) -> cc::Build {
 }
 
 fn generate_artifacts(src: &Path, rust_out: &Path, cpp_out: &Path) {
-    let source_code =
-        fs::read_to_string(src).expect(&format!("Failed to read source file: {:?}", src));
+    let source_code = fs::read_to_string(src)
+        .unwrap_or_else(|_| panic!("Failed to read source file: {:?}", src));
 
     let dsl_content =
         parser::extract_dsl(&source_code).expect("No bind! { ... } block found in source file");
 
-    let ast: BindInput = syn::parse_str(&dsl_content).expect("Failed to parse bind! DSL");
+    let ast: BindInput = syn::parse_str(dsl_content).expect("Failed to parse bind! DSL");
 
     let bind_context = preprocess(&ast);
 
@@ -186,12 +486,11 @@ fn generate_artifacts(src: &Path, rust_out: &Path, cpp_out: &Path) {
 }
 
 fn write_if_changed(path: &Path, content: &str) {
-    if path.exists() {
-        if let Ok(existing) = fs::read_to_string(path) {
-            if existing == content {
-                return;
-            }
-        }
+    if path.exists()
+        && let Ok(existing) = fs::read_to_string(path)
+        && existing == content
+    {
+        return;
     }
-    fs::write(path, content).expect(&format!("Failed to write to {:?}", path));
+    fs::write(path, content).unwrap_or_else(|_| panic!("Failed to write to {:?}", path));
 }
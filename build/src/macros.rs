@@ -1,9 +1,14 @@
 pub(crate) const CONTENT: &str = r#"
 #include "rust/cxx.h"
+#include <algorithm>
+#include <cstddef>
 #include <memory>
+#include <optional>
+#include <sstream>
 #include <stdexcept>
 #include <string>
 #include <type_traits>
+#include <unordered_map>
 #include <utility>
 #include <vector>
 
@@ -43,6 +48,10 @@ namespace bridge_detail {
     template <typename T> struct is_optional : std::false_type {};
     template <typename T> struct is_optional<std::optional<T>> : std::true_type {};
 
+    // classes declared with #[contiguous], eigen dense types qualify as well
+    template <typename T>
+    struct is_contiguous : is_eigen_dense<T> {};
+
     template <typename T>
     struct is_general_ref_type {
         using U = typename std::decay_t<T>;
@@ -55,7 +64,7 @@ namespace bridge_detail {
             !is_std_map<U>::value &&      
             !is_unique_ptr<U>::value &&   
             !is_optional<U>::value &&  
-            !is_eigen_dense<U>::value;
+            !is_contiguous<U>::value;
     };
 
     // string must copy 
@@ -178,13 +187,29 @@ namespace bridge_detail {
         return return_convert(std::move(*opt)); 
     }
 
+    // contiguous storage is viewed through a slice
     template <typename T>
-    inline typename std::enable_if_t<
-        is_eigen_dense<T>::value && 
-        std::is_arithmetic_v<typename T::Scalar>, 
-        rust::Slice<const typename T::Scalar>>
+    using contiguous_elem_t = std::remove_pointer_t<decltype(std::declval<T&>().data())>;
+
+    template <typename T>
+    inline typename std::enable_if_t<is_contiguous<T>::value, rust::Slice<contiguous_elem_t<const T>>>
     return_convert(const T& v) {
-        return rust::Slice<const typename T::Scalar>(v.data(), v.size());
+        return rust::Slice<contiguous_elem_t<const T>>(v.data(), static_cast<size_t>(v.size()));
+    }
+
+    template <typename T>
+    inline typename std::enable_if_t<is_contiguous<T>::value, rust::Slice<contiguous_elem_t<T>>>
+    return_convert(T& v) {
+        return rust::Slice<contiguous_elem_t<T>>(v.data(), static_cast<size_t>(v.size()));
+    }
+
+    // contiguous owned must return unique_ptr
+    template <typename T>
+    inline typename std::enable_if_t<
+        is_contiguous<T>::value && !std::is_reference_v<T>,
+        std::unique_ptr<T>>
+    return_convert(T&& v) {
+        return std::make_unique<T>(std::move(v));
     }
     
     // string must copy 
@@ -208,6 +233,11 @@ namespace bridge_detail {
         arg_convert(rust::Slice<T> slice) {
         return std::vector<T>(slice.begin(), slice.end());
     }
+    // &[&str] need reconstruct vector<string>
+    inline std::vector<std::string> arg_convert(rust::Slice<const rust::Str> slice) {
+        return std::vector<std::string>(slice.begin(), slice.end());
+    }
+
     // unique_ptr<T> 
     template <typename T>
     inline T arg_convert(std::unique_ptr<T> ptr) {
@@ -223,6 +253,7 @@ namespace bridge_detail {
 
     template <typename T> inline T& force_mut_ref(T& val) { return val; }
     template <typename T> inline T& force_mut_ref(std::unique_ptr<T>& ptr) { return *ptr; }
+    template <typename T> inline const T& force_mut_ref(const std::unique_ptr<T>& ptr) { return *ptr; }
 
     template <typename L, typename R>
     inline typename std::enable_if_t<is_unique_ptr<L>::value>
@@ -263,6 +294,108 @@ namespace bridge_detail {
         push_smart(std::vector<T>& vec, Arg&& val) {
         vec.push_back(arg_convert(std::forward<Arg>(val)));
     }
+
+    // build a container slot of type L from an FFI argument
+    template <typename L, typename R>
+    inline typename std::enable_if_t<is_unique_ptr<L>::value, L>
+    into_smart(R&& rhs) {
+        return std::forward<R>(rhs);
+    }
+
+    template <typename L, typename R>
+    inline typename std::enable_if_t<!is_unique_ptr<L>::value && is_unique_ptr<std::decay_t<R>>::value, L>
+    into_smart(R&& rhs) {
+        return std::move(*rhs);
+    }
+
+    template <typename L, typename R>
+    inline typename std::enable_if_t<!is_unique_ptr<L>::value && !is_unique_ptr<std::decay_t<R>>::value, L>
+    into_smart(R&& rhs) {
+        return arg_convert(std::forward<R>(rhs));
+    }
+
+    // move an element out of a container into its FFI return form
+    template <typename T>
+    inline std::unique_ptr<T> take_smart(std::unique_ptr<T>& val) {
+        return std::move(val);
+    }
+
+    inline rust::String take_smart(std::string& val) {
+        return rust::String(val);
+    }
+
+    template <typename T>
+    inline typename std::enable_if_t<std::is_arithmetic_v<T> || std::is_enum_v<T>, T>
+    take_smart(T& val) {
+        return val;
+    }
+
+    template <typename T>
+    inline typename std::enable_if_t<
+        !std::is_arithmetic_v<T> && !std::is_enum_v<T> && !is_string<T>::value &&
+            !is_unique_ptr<T>::value,
+        std::unique_ptr<T>>
+    take_smart(T& val) {
+        return std::make_unique<T>(std::move(val));
+    }
+
+    // copy an element out of a container into its FFI return form
+    template <typename T>
+    inline auto copy_out(const T& val) {
+        if constexpr (is_string<T>::value)
+            return rust::String(val);
+        else
+            return val;
+    }
+
+    // aggregate members of `from_init`; null objects are value-initialized
+    template <typename M, typename A>
+    inline M init_member(A arg) {
+        if constexpr (is_unique_ptr<A>::value) {
+            if (!arg)
+                return M{};
+            return M(std::move(*arg));
+        } else {
+            return arg_convert(std::move(arg));
+        }
+    }
+
+    // positional `from_init` below C++20: whether member `Next`, at offset
+    // `next`, is the one declared right after `Prev` at offset `prev`
+    template <typename Prev, typename Next>
+    constexpr bool init_follows(size_t prev, size_t next) {
+        size_t end = prev + sizeof(Prev);
+        return next == (end + alignof(Next) - 1) / alignof(Next) * alignof(Next);
+    }
+
+    template <typename M, typename A>
+    inline M init_optional(bool present, A arg) {
+        if (!present)
+            return M{};
+        return M(arg_convert(std::move(arg)));
+    }
+
+    // selects the _Exposer constructor that forwards to protected base constructors
+    struct protected_ctor_t {};
+    inline constexpr protected_ctor_t protected_ctor{};
+
+    // selects the _Exposer constructor that takes over a base object
+    struct from_base_t {};
+    inline constexpr from_base_t from_base{};
+
+    // a base object is moved into a new _Exposer, it cannot be viewed as
+    // one; for other classes this is the identity
+    template <typename Exposer, typename T>
+    inline std::unique_ptr<Exposer> into_exposer(std::unique_ptr<T> ptr) {
+        if constexpr (std::is_same_v<Exposer, T>) {
+            return ptr;
+        } else {
+            static_assert(std::is_base_of_v<T, Exposer>, "factory must return the exposed class");
+            if (!ptr)
+                return nullptr;
+            return std::make_unique<Exposer>(from_base, std::move(*ptr));
+        }
+    }
 } // namespace bridge_detail
 
 
@@ -278,78 +411,179 @@ using f32 = float;
 using f64 = double;
 using String = std::string;
 
-#define DEFINE_VAL(CLASS, FIELD)                                               \
+#define DEFINE_VAL(CLASS, FIELD, MEMBER)                                       \
     inline auto CLASS##_get_##FIELD(const CLASS &obj)                          \
-        -> decltype(::bridge_detail::return_convert(obj.FIELD)) {              \
-        return ::bridge_detail::return_convert(obj.FIELD);                     \
+        -> decltype(::bridge_detail::return_convert(obj.MEMBER)) {             \
+        return ::bridge_detail::return_convert(obj.MEMBER);                    \
     }
 
-#define DEFINE_OBJ(CLASS, FIELD)                                               \
+#define DEFINE_OBJ(CLASS, FIELD, MEMBER)                                       \
     inline auto CLASS##_get_##FIELD(CLASS &obj)                                \
-        -> decltype(::bridge_detail::return_convert(obj.FIELD)) {              \
-        return ::bridge_detail::return_convert(obj.FIELD);                     \
+        -> decltype(::bridge_detail::return_convert(obj.MEMBER)) {             \
+        return ::bridge_detail::return_convert(obj.MEMBER);                    \
     }
 
-#define DEFINE_OBJ_CONST(CLASS, FIELD)                                         \
+#define DEFINE_OBJ_CONST(CLASS, FIELD, MEMBER)                                 \
     inline auto CLASS##_get_##FIELD(const CLASS &obj)                          \
-        -> decltype(::bridge_detail::return_convert(obj.FIELD)) {              \
-        return ::bridge_detail::return_convert(obj.FIELD);                     \
+        -> decltype(::bridge_detail::return_convert(obj.MEMBER)) {             \
+        return ::bridge_detail::return_convert(obj.MEMBER);                    \
     }
 
-#define DEFINE_OBJ_SET(CLASS, FIELD)                                           \
+#define DEFINE_OBJ_SET(CLASS, FIELD, MEMBER)                                   \
     template <typename Arg>                                                    \
     inline void CLASS##_set_##FIELD(CLASS &obj, Arg val) {                     \
-        ::bridge_detail::assign_smart(obj.FIELD, std::move(val));              \
+        ::bridge_detail::assign_smart(obj.MEMBER, std::move(val));             \
+    }
+
+#define DEFINE_SLICE_MUT(CLASS, FIELD, MEMBER)                                 \
+    inline auto CLASS##_get_##FIELD##_mut(CLASS &obj)                          \
+        -> decltype(::bridge_detail::return_convert(obj.MEMBER)) {             \
+        return ::bridge_detail::return_convert(obj.MEMBER);                    \
+    }
+
+#define DEFINE_CONTIGUOUS(CLASS)                                               \
+    namespace bridge_detail {                                                  \
+        template <> struct is_contiguous<CLASS> : std::true_type {};           \
+    }
+
+#define DEFINE_CONTIGUOUS_OPS(CLASS)                                           \
+    inline rust::Slice<::bridge_detail::contiguous_elem_t<const CLASS>>        \
+    CLASS##_as_slice(const CLASS &self) {                                      \
+        return {self.data(), static_cast<size_t>(self.size())};                \
+    }                                                                          \
+    inline rust::Slice<::bridge_detail::contiguous_elem_t<CLASS>>              \
+    CLASS##_as_mut_slice(CLASS &self) {                                        \
+        return {self.data(), static_cast<size_t>(self.size())};                \
     }
 
-#define DEFINE_VAL_SET(CLASS, FIELD)                                           \
+#define DEFINE_CONTIGUOUS_RESIZE(CLASS)                                        \
+    inline void CLASS##_slice_resize(CLASS &self, size_t len) {                \
+        self.resize(len);                                                      \
+    }
+
+#define DEFINE_VAL_SET(CLASS, FIELD, MEMBER)                                   \
     template <typename T>                                                      \
     inline void CLASS##_set_##FIELD(CLASS &obj, T val) {                       \
-        obj.FIELD = ::bridge_detail::arg_convert(val);                         \
+        obj.MEMBER = ::bridge_detail::arg_convert(val);                        \
+    }
+
+// fields kept behind C++ accessor methods
+#define DEFINE_GETTER(CLASS, FIELD, GETTER)                                    \
+    inline auto CLASS##_get_##FIELD(const CLASS &obj)                          \
+        -> decltype(::bridge_detail::return_convert(obj.GETTER())) {           \
+        return ::bridge_detail::return_convert(obj.GETTER());                  \
     }
 
-#define DEFINE_ITER(CLASS, FIELD, ITEM_TYPE)                                          \
-    struct CLASS##_##FIELD##_IterCtx {                                               \
-        using IterType = decltype(std::declval<CLASS &>().begin());            \
+#define DEFINE_SETTER(CLASS, FIELD, SETTER)                                    \
+    template <typename Arg>                                                    \
+    inline void CLASS##_set_##FIELD(CLASS &obj, Arg val) {                     \
+        if constexpr (::bridge_detail::is_unique_ptr<Arg>::value)              \
+            obj.SETTER(std::move(*val));                                       \
+        else                                                                   \
+            obj.SETTER(::bridge_detail::arg_convert(val));                     \
+    }
+
+// RANGE is an expression over `obj`; a range returned by value is kept alive
+// alongside its iterators, so only iterators that copy or move the elements
+// out can use one
+#define DEFINE_ITER_CTX(CLASS, NAME, SELF_TYPE, RANGE, BEGIN, END)             \
+    inline decltype(auto) CLASS##_##NAME##_iter_range(SELF_TYPE &obj) {        \
+        return (RANGE);                                                        \
+    }                                                                          \
+    struct CLASS##_##NAME##_IterCtx {                                          \
+        using RangeType =                                                      \
+            decltype(CLASS##_##NAME##_iter_range(std::declval<SELF_TYPE &>())); \
+        using IterType = decltype(std::declval<RangeType &>().BEGIN());        \
+        RangeType range;                                                       \
         IterType cur;                                                          \
         IterType end;                                                          \
-        CLASS##_##FIELD##_IterCtx(CLASS &obj) : cur(obj.begin()), end(obj.end()) {}  \
+        CLASS##_##NAME##_IterCtx(SELF_TYPE &obj)                               \
+            : range(CLASS##_##NAME##_iter_range(obj)),                         \
+              cur(range.BEGIN()), end(range.END()) {}                          \
     };                                                                         \
-    inline std::unique_ptr<CLASS##_##FIELD##_IterCtx> CLASS##_##FIELD##_iter_new(         \
-        CLASS &obj) {                                                          \
-        return std::make_unique<CLASS##_##FIELD##_IterCtx>(obj);                     \
+    inline std::unique_ptr<CLASS##_##NAME##_IterCtx> CLASS##_##NAME##_iter_new( \
+        SELF_TYPE &obj) {                                                      \
+        return std::make_unique<CLASS##_##NAME##_IterCtx>(obj);                \
     }                                                                          \
-    inline std::unique_ptr<ITEM_TYPE> CLASS##_##FIELD##_iter_next(                  \
-        CLASS##_##FIELD##_IterCtx &ctx) {                                   \
-        if (ctx.cur == ctx.end)                                                \
-            return nullptr;                                                    \
-        auto ptr = std::make_unique<ITEM_TYPE>(std::move(*ctx.cur));           \
-        ++ctx.cur;                                                             \
-        return ptr;                                                            \
+    inline bool CLASS##_##NAME##_iter_done(const CLASS##_##NAME##_IterCtx &ctx) { \
+        return ctx.cur == ctx.end;                                             \
+    }
+
+// the yielded references outlive the ctx, so the range must live in `obj`
+#define DEFINE_ITER_BORROWED(CLASS, NAME)                                      \
+    static_assert(std::is_lvalue_reference_v<CLASS##_##NAME##_IterCtx::RangeType>, \
+                  "Borrowing #[iter] " #CLASS "::" #NAME " needs a range that is " \
+                  "a reference into the object, not a value; use #[iter(drain)]");
+
+// yields references into the range
+#define DEFINE_ITER(CLASS, NAME, RANGE, BEGIN, END)                            \
+    DEFINE_ITER_CTX(CLASS, NAME, const CLASS, RANGE, BEGIN, END)               \
+    DEFINE_ITER_BORROWED(CLASS, NAME)                                          \
+    inline decltype(auto) CLASS##_##NAME##_iter_next(CLASS##_##NAME##_IterCtx &ctx) { \
+        return ::bridge_detail::force_mut_ref(*ctx.cur++);                     \
+    }
+
+#define DEFINE_ITER_MUT(CLASS, NAME, RANGE, BEGIN, END)                        \
+    DEFINE_ITER_CTX(CLASS, NAME, CLASS, RANGE, BEGIN, END)                     \
+    DEFINE_ITER_BORROWED(CLASS, NAME)                                          \
+    inline decltype(auto) CLASS##_##NAME##_iter_next(CLASS##_##NAME##_IterCtx &ctx) { \
+        return ::bridge_detail::force_mut_ref(*ctx.cur++);                     \
+    }
+
+#define DEFINE_ITER_NEXT_BACK(CLASS, NAME)                                     \
+    inline decltype(auto) CLASS##_##NAME##_iter_next_back(CLASS##_##NAME##_IterCtx &ctx) { \
+        return ::bridge_detail::force_mut_ref(*--ctx.end);                     \
+    }
+
+// moves every element out, leaving them in a moved-from state
+#define DEFINE_ITER_DRAIN(CLASS, NAME, RANGE, BEGIN, END)                      \
+    DEFINE_ITER_CTX(CLASS, NAME, CLASS, RANGE, BEGIN, END)                     \
+    inline auto CLASS##_##NAME##_iter_next(CLASS##_##NAME##_IterCtx &ctx) {    \
+        return ::bridge_detail::take_smart(*ctx.cur++);                        \
+    }
+
+#define DEFINE_ITER_DRAIN_BACK(CLASS, NAME)                                    \
+    inline auto CLASS##_##NAME##_iter_next_back(CLASS##_##NAME##_IterCtx &ctx) { \
+        return ::bridge_detail::take_smart(*--ctx.end);                        \
+    }
+
+// copies primitives, strings and enums out, leaving the range untouched
+#define DEFINE_ITER_COPY(CLASS, NAME, RANGE, BEGIN, END)                       \
+    DEFINE_ITER_CTX(CLASS, NAME, const CLASS, RANGE, BEGIN, END)               \
+    inline auto CLASS##_##NAME##_iter_next(CLASS##_##NAME##_IterCtx &ctx) {    \
+        return ::bridge_detail::copy_out(*ctx.cur++);                          \
+    }
+
+#define DEFINE_ITER_COPY_BACK(CLASS, NAME)                                     \
+    inline auto CLASS##_##NAME##_iter_next_back(CLASS##_##NAME##_IterCtx &ctx) { \
+        return ::bridge_detail::copy_out(*--ctx.end);                          \
     }
 
-#define DEFINE_OPT_VAL(CLASS, FIELD)                                           \
+#define DEFINE_OPT_VAL(CLASS, FIELD, MEMBER)                                   \
     inline auto CLASS##_get_##FIELD(const CLASS &obj)                          \
-        -> decltype(::bridge_detail::return_convert(*obj.FIELD)) {             \
-        if (!obj.FIELD)                                                        \
+        -> decltype(::bridge_detail::return_convert(*obj.MEMBER)) {            \
+        if (!obj.MEMBER)                                                       \
             throw std::runtime_error(#FIELD " is nullopt");                    \
-        return ::bridge_detail::return_convert(*obj.FIELD);                    \
+        return ::bridge_detail::return_convert(*obj.MEMBER);                   \
     }
 
-#define DEFINE_OPT_OBJ(CLASS, FIELD)                                           \
+#define DEFINE_OPT_OBJ(CLASS, FIELD, MEMBER)                                   \
     inline auto CLASS##_get_##FIELD(CLASS &obj)                          \
-        -> decltype(::bridge_detail::return_convert(*obj.FIELD)) {             \
-        if (!obj.FIELD)                                                        \
+        -> decltype(::bridge_detail::return_convert(*obj.MEMBER)) {            \
+        if (!obj.MEMBER)                                                       \
             throw std::runtime_error(#FIELD " is nullopt");                    \
-        return ::bridge_detail::return_convert(*obj.FIELD);                    \
+        return ::bridge_detail::return_convert(*obj.MEMBER);                   \
     }
 
-#define DEFINE_OPT_OBJ_CONST(CLASS, FIELD)                                         \
+#define DEFINE_OPT_CLEAR(CLASS, FIELD, MEMBER)                                 \
+    inline void CLASS##_clear_##FIELD(CLASS &obj) { obj.MEMBER.reset(); }
+
+#define DEFINE_OPT_OBJ_CONST(CLASS, FIELD, MEMBER)                                 \
     inline auto CLASS##_get_##FIELD(const CLASS &obj)                          \
-        -> decltype(::bridge_detail::return_convert(*obj.FIELD)) {             \
-        if (!obj.FIELD)                                                        \
+        -> decltype(::bridge_detail::return_convert(*obj.MEMBER)) {            \
+        if (!obj.MEMBER)                                                       \
             throw std::runtime_error(#FIELD " is nullopt");                    \
-        return ::bridge_detail::return_convert(*obj.FIELD);                    \
+        return ::bridge_detail::return_convert(*obj.MEMBER);                   \
     }
 
 
@@ -377,6 +611,19 @@ using String = std::string;
         } \
     }
 
+#define DEFINE_METHOD_CONSUME(CLASS, RUST_NAME, CPP_METHOD) \
+    template <typename... Args> \
+    inline decltype(auto) CLASS##_method_##RUST_NAME(std::unique_ptr<CLASS> obj, Args... args) { \
+        if (!obj) throw std::runtime_error("Receiver is null"); \
+        if constexpr (std::is_void_v<decltype(std::move(*obj).CPP_METHOD(::bridge_detail::arg_convert(std::forward<Args>(args))...))>) { \
+            std::move(*obj).CPP_METHOD(::bridge_detail::arg_convert(std::forward<Args>(args))...); \
+        } else { \
+            return ::bridge_detail::return_convert( \
+                std::move(*obj).CPP_METHOD(::bridge_detail::arg_convert(std::forward<Args>(args))...) \
+            ); \
+        } \
+    }
+
 #define DEFINE_OP_CALL(CLASS, RUST_NAME)                                       \
     template <typename... Args>                                                \
     inline auto CLASS##_method_##RUST_NAME(CLASS &obj, Args... args)           \
@@ -404,6 +651,61 @@ using String = std::string;
         return std::make_unique<CLASS>(::bridge_detail::arg_convert(args)...); \
     }
 
+#define DEFINE_PROTECTED_CTOR(CLASS, FUNC_NAME)                                \
+    template <typename... Args>                                                \
+    inline std::unique_ptr<CLASS> make_##CLASS##_##FUNC_NAME(Args... args) {   \
+        return std::make_unique<CLASS>(::bridge_detail::protected_ctor,        \
+                                       ::bridge_detail::arg_convert(args)...); \
+    }
+
+#define DEFINE_EXPOSER_FACTORY(CLASS, FUNC_NAME, FACTORY)                      \
+    template <typename... Args>                                                \
+    inline std::unique_ptr<CLASS> make_##CLASS##_##FUNC_NAME(Args... args) {   \
+        return ::bridge_detail::into_exposer<CLASS>(                           \
+            FACTORY(::bridge_detail::arg_convert(args)...));                   \
+    }
+
+// a null result is thrown so that it surfaces as an `Err`
+#define DEFINE_CHECKED_FACTORY(CLASS, FUNC_NAME, FACTORY)                      \
+    template <typename... Args>                                                \
+    inline std::unique_ptr<CLASS> make_##CLASS##_##FUNC_NAME(Args... args) {   \
+        auto ptr = FACTORY(::bridge_detail::arg_convert(args)...);             \
+        if (!ptr)                                                              \
+            throw std::runtime_error(#FACTORY " returned null");               \
+        return ::bridge_detail::into_exposer<CLASS>(std::move(ptr));           \
+    }
+
+#define DEFINE_CLONE(CLASS)                                                    \
+    inline std::unique_ptr<CLASS> CLASS##_clone(const CLASS &obj) {            \
+        return std::make_unique<CLASS>(obj);                                   \
+    }
+
+#define DEFINE_VEC_FROM_REFS(VEC_TYPE)                                         \
+    template <typename T>                                                      \
+    inline std::unique_ptr<VEC_TYPE> VEC_TYPE##_from_refs(                     \
+        rust::Slice<const T *const> items) {                                   \
+        auto vec = std::make_unique<VEC_TYPE>();                               \
+        vec->reserve(items.size());                                            \
+        for (const T *item : items) vec->push_back(*item);                     \
+        return vec;                                                            \
+    }
+
+#define DEFINE_DISPLAY(CLASS)                                                  \
+    inline rust::String CLASS##_to_string(const CLASS &obj) {                  \
+        std::ostringstream os;                                                 \
+        os << obj;                                                             \
+        return rust::String(os.str());                                         \
+    }
+
+#define DEFINE_FROM_STR(CLASS)                                                 \
+    inline std::unique_ptr<CLASS> CLASS##_from_str(rust::Str s) {              \
+        std::istringstream is{std::string(s)};                                 \
+        auto obj = std::make_unique<CLASS>();                                  \
+        if (!(is >> *obj) || !(is >> std::ws).eof())                           \
+            throw std::invalid_argument("failed to parse " #CLASS);            \
+        return obj;                                                            \
+    }
+
 #define DEFINE_VEC_LEN(VEC_TYPE) \
     inline size_t VEC_TYPE##_len(const VEC_TYPE& self) { return self.size(); }
 
@@ -419,14 +721,14 @@ using String = std::string;
 
 #define DEFINE_VEC_AS_SLICE(VEC_TYPE, ELEM_TYPE) \
     template <typename T = ELEM_TYPE> \
-    inline typename std::enable_if_t<std::is_arithmetic_v<T>, rust::Slice<const T>> \
+    inline typename std::enable_if_t<std::is_arithmetic_v<T> || std::is_enum_v<T>, rust::Slice<const T>> \
     VEC_TYPE##_as_slice(const VEC_TYPE& self) { \
         return rust::Slice<const T>(self.data(), self.size()); \
     }
 
 #define DEFINE_VEC_AS_MUT_SLICE(VEC_TYPE, ELEM_TYPE) \
     template <typename T = ELEM_TYPE> \
-    inline typename std::enable_if_t<std::is_arithmetic_v<T>, rust::Slice<T>> \
+    inline typename std::enable_if_t<std::is_arithmetic_v<T> || std::is_enum_v<T>, rust::Slice<T>> \
     VEC_TYPE##_as_mut_slice(VEC_TYPE& self) { \
         return rust::Slice<T>(self.data(), self.size()); \
     }
@@ -443,6 +745,63 @@ using String = std::string;
         ::bridge_detail::assign_smart(self[i], std::move(val)); \
     }
 
+#define DEFINE_VEC_MODIFY(VEC_TYPE) \
+    inline void VEC_TYPE##_clear(VEC_TYPE& self) { self.clear(); } \
+    inline void VEC_TYPE##_truncate(VEC_TYPE& self, size_t len) { \
+        if (len < self.size()) self.erase(self.begin() + len, self.end()); \
+    } \
+    inline void VEC_TYPE##_reserve(VEC_TYPE& self, size_t additional) { \
+        self.reserve(self.size() + additional); \
+    } \
+    inline size_t VEC_TYPE##_capacity(const VEC_TYPE& self) { return self.capacity(); } \
+    inline void VEC_TYPE##_swap(VEC_TYPE& self, size_t a, size_t b) { \
+        std::swap(self[a], self[b]); \
+    } \
+    inline auto VEC_TYPE##_pop(VEC_TYPE& self) { \
+        auto val = ::bridge_detail::take_smart(self.back()); \
+        self.pop_back(); \
+        return val; \
+    } \
+    inline auto VEC_TYPE##_remove(VEC_TYPE& self, size_t i) { \
+        auto val = ::bridge_detail::take_smart(self[i]); \
+        self.erase(self.begin() + i); \
+        return val; \
+    } \
+    inline auto VEC_TYPE##_swap_remove(VEC_TYPE& self, size_t i) { \
+        auto val = ::bridge_detail::take_smart(self[i]); \
+        if (i + 1 != self.size()) self[i] = std::move(self.back()); \
+        self.pop_back(); \
+        return val; \
+    } \
+    template <typename Arg> \
+    inline void VEC_TYPE##_insert(VEC_TYPE& self, size_t i, Arg val) { \
+        ::bridge_detail::push_smart(self, std::move(val)); \
+        std::rotate(self.begin() + i, self.end() - 1, self.end()); \
+    } \
+    inline void VEC_TYPE##_retain_mask(VEC_TYPE& self, rust::Slice<const bool> keep) { \
+        size_t w = 0; \
+        for (size_t r = 0; r < self.size(); ++r) { \
+            if (!keep[r]) continue; \
+            if (w != r) self[w] = std::move(self[r]); \
+            ++w; \
+        } \
+        self.erase(self.begin() + w, self.end()); \
+    }
+
+#define DEFINE_VEC_BULK(VEC_TYPE) \
+    template <typename T> \
+    inline void VEC_TYPE##_extend_from_slice(VEC_TYPE& self, rust::Slice<const T> vals) { \
+        self.reserve(self.size() + vals.size()); \
+        for (const auto& v : vals) self.push_back(::bridge_detail::arg_convert(v)); \
+    } \
+    template <typename V = VEC_TYPE> \
+    inline rust::Vec<rust::String> VEC_TYPE##_to_vec(const V& self) { \
+        rust::Vec<rust::String> out; \
+        out.reserve(self.size()); \
+        for (const auto& s : self) out.push_back(rust::String(s)); \
+        return out; \
+    }
+
 #define DEFINE_VEC_CTOR(VEC_TYPE) \
     inline std::unique_ptr<VEC_TYPE> make_##VEC_TYPE##_new() { \
         return std::make_unique<VEC_TYPE>(); \
@@ -456,8 +815,64 @@ using String = std::string;
     DEFINE_VEC_AS_SLICE(VEC_TYPE, ELEM_TYPE) \
     DEFINE_VEC_AS_MUT_SLICE(VEC_TYPE, ELEM_TYPE) \
     DEFINE_VEC_SET(VEC_TYPE, ELEM_TYPE) \
+    DEFINE_VEC_MODIFY(VEC_TYPE) \
+    DEFINE_VEC_BULK(VEC_TYPE) \
     DEFINE_VEC_CTOR(VEC_TYPE) \
 
+// std::vector<bool> is bit-packed: no data(), no element references
+#define DEFINE_VEC_BOOL_OPS(VEC_TYPE) \
+    DEFINE_VEC_LEN(VEC_TYPE) \
+    DEFINE_VEC_GET(VEC_TYPE) \
+    DEFINE_VEC_PUSH(VEC_TYPE, bool) \
+    DEFINE_VEC_CTOR(VEC_TYPE) \
+    inline void VEC_TYPE##_set(VEC_TYPE& self, size_t i, bool val) { self[i] = val; } \
+    inline void VEC_TYPE##_clear(VEC_TYPE& self) { self.clear(); } \
+    inline void VEC_TYPE##_truncate(VEC_TYPE& self, size_t len) { \
+        if (len < self.size()) self.resize(len); \
+    } \
+    inline void VEC_TYPE##_reserve(VEC_TYPE& self, size_t additional) { \
+        self.reserve(self.size() + additional); \
+    } \
+    inline size_t VEC_TYPE##_capacity(const VEC_TYPE& self) { return self.capacity(); } \
+    inline void VEC_TYPE##_swap(VEC_TYPE& self, size_t a, size_t b) { \
+        VEC_TYPE::swap(self[a], self[b]); \
+    } \
+    inline bool VEC_TYPE##_pop(VEC_TYPE& self) { \
+        bool val = self.back(); \
+        self.pop_back(); \
+        return val; \
+    } \
+    inline bool VEC_TYPE##_remove(VEC_TYPE& self, size_t i) { \
+        bool val = self[i]; \
+        self.erase(self.begin() + i); \
+        return val; \
+    } \
+    inline bool VEC_TYPE##_swap_remove(VEC_TYPE& self, size_t i) { \
+        bool val = self[i]; \
+        self[i] = self.back(); \
+        self.pop_back(); \
+        return val; \
+    } \
+    inline void VEC_TYPE##_insert(VEC_TYPE& self, size_t i, bool val) { \
+        self.insert(self.begin() + i, val); \
+    } \
+    inline void VEC_TYPE##_retain_mask(VEC_TYPE& self, rust::Slice<const bool> keep) { \
+        size_t w = 0; \
+        for (size_t r = 0; r < self.size(); ++r) { \
+            if (keep[r]) self[w++] = static_cast<bool>(self[r]); \
+        } \
+        self.resize(w); \
+    } \
+    inline void VEC_TYPE##_extend_from_slice(VEC_TYPE& self, rust::Slice<const bool> vals) { \
+        self.insert(self.end(), vals.begin(), vals.end()); \
+    } \
+    inline rust::Vec<bool> VEC_TYPE##_to_vec(const VEC_TYPE& self) { \
+        rust::Vec<bool> out; \
+        out.reserve(self.size()); \
+        for (bool b : self) out.push_back(b); \
+        return out; \
+    }
+
 #define DEFINE_MAP_ITER(MAP_TYPE) \
     struct MAP_TYPE##_IterCtx { \
         using IterType = typename MAP_TYPE::iterator; \
@@ -474,6 +889,9 @@ using String = std::string;
     inline decltype(auto) MAP_TYPE##_iter_val(MAP_TYPE##_IterCtx& ctx) { \
         return ::bridge_detail::return_convert(ctx.cur->second); \
     } \
+    inline decltype(auto) MAP_TYPE##_iter_val_mut(MAP_TYPE##_IterCtx& ctx) { \
+        return ::bridge_detail::force_mut_ref(ctx.cur->second); \
+    } \
     inline void MAP_TYPE##_iter_step(MAP_TYPE##_IterCtx& ctx) { \
         ++ctx.cur; \
     } \
@@ -487,24 +905,96 @@ using String = std::string;
 #define DEFINE_MAP_GET(MAP_TYPE) \
     template <typename ArgKey> \
     inline decltype(auto) MAP_TYPE##_get(MAP_TYPE& self, ArgKey key) { \
-        auto cpp_key = ::bridge_detail::arg_convert(key); \
-        auto it = self.find(cpp_key); \
+        auto it = self.find(::bridge_detail::arg_convert(key)); \
         if (it == self.end()) throw std::out_of_range("Key not found"); \
         return ::bridge_detail::return_convert(it->second); \
     }
 
-#define DEFINE_MAP_INSERT(MAP_TYPE, KEY_TYPE, VAL_TYPE) \
+#define DEFINE_MAP_GET_MUT(MAP_TYPE) \
+    template <typename ArgKey> \
+    inline decltype(auto) MAP_TYPE##_get_mut(MAP_TYPE& self, ArgKey key) { \
+        auto it = self.find(::bridge_detail::arg_convert(key)); \
+        if (it == self.end()) throw std::out_of_range("Key not found"); \
+        return ::bridge_detail::force_mut_ref(it->second); \
+    }
+
+// objects return the replaced value, or null for a new key; other values
+// report a replacement and hand the old value back through `old`
+#define DEFINE_MAP_INSERT(MAP_TYPE) \
     template <typename KeyArg, typename ValArg> \
-    inline void MAP_TYPE##_insert(MAP_TYPE& self, KeyArg&& key, ValArg&& val) { \
-        self.insert_or_assign( \
-            ::bridge_detail::arg_convert(std::forward<KeyArg>(key)), \
-            ::bridge_detail::arg_convert(std::forward<ValArg>(val)) \
-        ); \
+    inline auto MAP_TYPE##_insert(MAP_TYPE& self, KeyArg key, ValArg val) { \
+        using Mapped = typename MAP_TYPE::mapped_type; \
+        using Old = decltype(::bridge_detail::take_smart(std::declval<Mapped&>())); \
+        auto it = self.find(::bridge_detail::arg_convert(key)); \
+        if (it == self.end()) { \
+            self.emplace( \
+                ::bridge_detail::arg_convert(key), \
+                ::bridge_detail::into_smart<Mapped>(std::move(val))); \
+            return Old(); \
+        } \
+        Old old = ::bridge_detail::take_smart(it->second); \
+        it->second = ::bridge_detail::into_smart<Mapped>(std::move(val)); \
+        return old; \
+    } \
+    template <typename KeyArg, typename ValArg, typename Old> \
+    inline bool MAP_TYPE##_insert(MAP_TYPE& self, KeyArg key, ValArg val, Old& old) { \
+        using Mapped = typename MAP_TYPE::mapped_type; \
+        auto it = self.find(::bridge_detail::arg_convert(key)); \
+        if (it == self.end()) { \
+            self.emplace( \
+                ::bridge_detail::arg_convert(key), \
+                ::bridge_detail::into_smart<Mapped>(std::move(val))); \
+            return false; \
+        } \
+        old = ::bridge_detail::take_smart(it->second); \
+        it->second = ::bridge_detail::into_smart<Mapped>(std::move(val)); \
+        return true; \
+    }
+
+#define DEFINE_MAP_MODIFY(MAP_TYPE) \
+    template <typename ArgKey> \
+    inline auto MAP_TYPE##_remove(MAP_TYPE& self, ArgKey key) { \
+        auto it = self.find(::bridge_detail::arg_convert(key)); \
+        if (it == self.end()) throw std::out_of_range("Key not found"); \
+        auto val = ::bridge_detail::take_smart(it->second); \
+        self.erase(it); \
+        return val; \
+    } \
+    template <typename ArgKey> \
+    inline bool MAP_TYPE##_contains_key(const MAP_TYPE& self, ArgKey key) { \
+        return self.count(::bridge_detail::arg_convert(key)) != 0; \
+    } \
+    inline void MAP_TYPE##_clear(MAP_TYPE& self) { self.clear(); }
+
+#define DEFINE_MAP_BULK(MAP_TYPE) \
+    template <typename K, typename V, typename M = MAP_TYPE> \
+    inline void MAP_TYPE##_to_vecs(const M& self, rust::Vec<K>& keys, rust::Vec<V>& vals) { \
+        keys.reserve(self.size()); \
+        vals.reserve(self.size()); \
+        for (const auto& [k, v] : self) { \
+            keys.push_back(::bridge_detail::return_convert(k)); \
+            vals.push_back(::bridge_detail::return_convert(v)); \
+        } \
+    } \
+    template <typename K, typename V> \
+    inline std::unique_ptr<MAP_TYPE> make_##MAP_TYPE##_from_vecs( \
+        const rust::Vec<K>& keys, const rust::Vec<V>& vals) { \
+        auto m = std::make_unique<MAP_TYPE>(); \
+        m->reserve(keys.size()); \
+        for (size_t i = 0; i < keys.size(); ++i) { \
+            m->insert_or_assign( \
+                ::bridge_detail::arg_convert(keys[i]), ::bridge_detail::arg_convert(vals[i])); \
+        } \
+        return m; \
     }
 
 #define DEFINE_MAP_OPS(MAP_TYPE) \
     DEFINE_MAP_LEN(MAP_TYPE) \
     DEFINE_MAP_GET(MAP_TYPE) \
+    DEFINE_MAP_GET_MUT(MAP_TYPE) \
+    DEFINE_MAP_INSERT(MAP_TYPE) \
+    DEFINE_MAP_MODIFY(MAP_TYPE) \
+    DEFINE_MAP_BULK(MAP_TYPE) \
     DEFINE_MAP_ITER(MAP_TYPE)\
     DEFINE_VEC_CTOR(MAP_TYPE)\
 "#;
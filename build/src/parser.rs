@@ -1,9 +1,11 @@
 use crate::ast::*;
 use crate::utils::*;
-use quote::ToTokens;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
-use syn::{Attribute, Ident, LitStr, Result, Token, Type, braced, parenthesized};
+use syn::{
+    Attribute, Expr, ExprLit, Ident, Lit, LitStr, Meta, Result, Token, Type, braced, bracketed,
+    parenthesized,
+};
 
 impl TryFrom<&syn::Type> for TypeKind {
     type Error = syn::Error;
@@ -31,13 +33,14 @@ fn parse_type_path(p: &syn::TypePath) -> Result<TypeKind> {
             "Vec" => parse_vec(args),
             "Map" => parse_map(args),
             "Option" => parse_option(args),
+            "Result" => parse_result(args),
             "UniquePtr" => parse_unique_ptr(args),
             _ => Err(syn::Error::new_spanned(p, "Unknown generic type")),
         };
     }
 
     match ident.as_str() {
-        "String" => Ok(TypeKind::String),
+        "String" | "str" => Ok(TypeKind::String),
         s if is_primitive(s) => Ok(TypeKind::Primitive(s.to_string())),
         s => Ok(TypeKind::Object(s.to_string())),
     }
@@ -62,7 +65,7 @@ fn parse_vec(args: &syn::AngleBracketedGenericArguments) -> Result<TypeKind> {
 
 fn parse_map(args: &syn::AngleBracketedGenericArguments) -> Result<TypeKind> {
     let (k, v) = get_double_args(args)?;
-    let (real_val, is_val_ptr) = extract_unique_ptr_info(&v)?;
+    let (real_val, is_val_ptr) = extract_unique_ptr_info(v)?;
     let key_kind = TypeKind::try_from(k)?;
     Ok(TypeKind::Map {
         key: Box::new(key_kind),
@@ -77,6 +80,12 @@ fn parse_option(args: &syn::AngleBracketedGenericArguments) -> Result<TypeKind>
     Ok(TypeKind::Option(Box::new(inner_ty_kind)))
 }
 
+fn parse_result(args: &syn::AngleBracketedGenericArguments) -> Result<TypeKind> {
+    let inner_ty = get_single_arg(args)?;
+    let inner_ty_kind = TypeKind::try_from(inner_ty)?;
+    Ok(TypeKind::Result(Box::new(inner_ty_kind)))
+}
+
 fn parse_unique_ptr(args: &syn::AngleBracketedGenericArguments) -> Result<TypeKind> {
     let inner_ty = get_single_arg(args)?;
     let inner_ty_kind = TypeKind::try_from(inner_ty)?;
@@ -84,7 +93,14 @@ fn parse_unique_ptr(args: &syn::AngleBracketedGenericArguments) -> Result<TypeKi
 }
 
 fn parse_type_slice(s: &syn::TypeSlice) -> Result<TypeKind> {
-    let inner_kind = TypeKind::try_from(&*s.elem)?;
+    let inner_kind = match TypeKind::try_from(&*s.elem)? {
+        // `&[&str]` and `&[String]` both cross as `&[&str]`
+        TypeKind::Reference { inner, is_mut: false } if *inner == TypeKind::String => {
+            TypeKind::String
+        }
+        kind @ (TypeKind::Primitive(_) | TypeKind::String | TypeKind::Object(_)) => kind,
+        _ => return Err(syn::Error::new_spanned(s, "Unsupported slice element type")),
+    };
     Ok(TypeKind::Slice(Box::new(inner_kind)))
 }
 
@@ -122,12 +138,22 @@ impl Parse for BindInput {
                 let path: LitStr = content.parse()?;
                 input.parse::<Token![;]>()?;
                 items.push(BindItem::Include(path));
-            } else if input.peek(Token![struct]) || input.peek(Token![#]) {
-                items.push(BindItem::Struct(input.parse()?));
+            } else if input.peek(Token![struct])
+                || input.peek(Token![enum])
+                || input.peek(Token![#])
+            {
+                // attributes come first, the keyword after them picks the item
+                let fork = input.fork();
+                fork.call(Attribute::parse_outer)?;
+                if fork.peek(Token![enum]) {
+                    items.push(BindItem::Enum(input.parse()?));
+                } else {
+                    items.push(BindItem::Struct(input.parse()?));
+                }
             } else if input.peek(Token![impl]) {
                 items.push(BindItem::Impl(input.parse()?));
             } else {
-                return Err(input.error("Expected include!, struct, or impl"));
+                return Err(input.error("Expected include!, struct, enum, or impl"));
             }
         }
         Ok(BindInput { items })
@@ -140,25 +166,135 @@ impl Parse for StructDef {
         input.parse::<Token![struct]>()?;
         let name: Ident = input.parse()?;
 
-        let content;
-        braced!(content in input);
+        // `struct Handle;` declares a class purely as a handle
+        let fields_parsed: Punctuated<FieldDef, Token![,]> = if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+            Punctuated::new()
+        } else {
+            let content;
+            braced!(content in input);
+            content.parse_terminated(FieldDef::parse, Token![,])?
+        };
+
+        let is_opaque = attrs.iter().any(|attr| attr.path().is_ident("opaque"));
+        if is_opaque && !fields_parsed.is_empty() {
+            return Err(syn::Error::new_spanned(&name, "#[opaque] classes cannot have fields"));
+        }
+        let no_default_ctor =
+            is_opaque || attrs.iter().any(|attr| attr.path().is_ident("no_ctor"));
 
-        let fields_parsed: Punctuated<FieldDef, Token![,]> =
-            content.parse_terminated(FieldDef::parse, Token![,])?;
+        let derives = parse_derive_attr(&attrs)?;
+        let debug_protected = parse_debug_attr(&attrs)?;
+        let is_display = parse_flag_attr(&attrs, "display")?;
+        let is_from_str = parse_flag_attr(&attrs, "from_str")?;
+        let contiguous = parse_contiguous_attr(&attrs)?;
 
         Ok(StructDef {
             attrs,
             name,
             fields: fields_parsed.into_iter().collect(),
+            derives,
+            debug_protected,
+            is_display,
+            is_from_str,
+            contiguous,
+            no_default_ctor,
+        })
+    }
+}
+
+impl Parse for EnumDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let item: syn::ItemEnum = input.parse()?;
+        if !item.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(&item.generics, "Enums cannot be generic"));
+        }
+
+        let mut repr = None;
+        for attr in &item.attrs {
+            if !attr.path().is_ident("repr") {
+                return Err(syn::Error::new_spanned(attr, "Unsupported enum attribute"));
+            }
+            let ty: Ident = attr.parse_args()?;
+            let is_int = matches!(
+                ty.to_string().as_str(),
+                "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64" | "isize" | "usize"
+            );
+            if !is_int {
+                return Err(syn::Error::new_spanned(ty, "Expected an integer type"));
+            }
+            repr = Some(ty);
+        }
+
+        if item.variants.is_empty() {
+            return Err(syn::Error::new_spanned(&item.ident, "Enums need at least one variant"));
+        }
+        for variant in &item.variants {
+            if !matches!(variant.fields, syn::Fields::Unit) || !variant.attrs.is_empty() {
+                return Err(syn::Error::new_spanned(variant, "Expected a plain enum variant"));
+            }
+        }
+
+        Ok(EnumDef {
+            repr: repr.unwrap_or_else(|| Ident::new("i32", item.ident.span())),
+            name: item.ident,
+            variants: item.variants.into_iter().collect(),
         })
     }
 }
 
+#[derive(Default)]
+struct FieldAttrs {
+    is_protected: bool,
+    is_readonly: bool,
+    get: Option<LitStr>,
+    set: Option<LitStr>,
+    rename: Option<LitStr>,
+}
+
+/// Field attributes may list several `key = "value"` pairs in one
+/// `#[...]`, which `Attribute::parse_outer` rejects.
+fn parse_field_attrs(input: ParseStream) -> Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    while input.peek(Token![#]) {
+        input.parse::<Token![#]>()?;
+        let content;
+        bracketed!(content in input);
+        for meta in Punctuated::<Meta, Token![,]>::parse_terminated(&content)? {
+            let slot = if meta.path().is_ident("protected") {
+                meta.require_path_only()?;
+                attrs.is_protected = true;
+                continue;
+            } else if meta.path().is_ident("readonly") {
+                meta.require_path_only()?;
+                attrs.is_readonly = true;
+                continue;
+            } else if meta.path().is_ident("get") {
+                &mut attrs.get
+            } else if meta.path().is_ident("set") {
+                &mut attrs.set
+            } else if meta.path().is_ident("rename") {
+                &mut attrs.rename
+            } else if meta.path().is_ident("doc") {
+                continue;
+            } else {
+                return Err(syn::Error::new_spanned(meta.path(), "Unknown field attribute"));
+            };
+            let value = &meta.require_name_value()?.value;
+            match value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => *slot = Some(lit.clone()),
+                _ => return Err(syn::Error::new_spanned(value, "Expected a string literal")),
+            }
+        }
+    }
+    Ok(attrs)
+}
+
 impl Parse for FieldDef {
     fn parse(input: ParseStream) -> Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
-        let is_protected = attrs.iter().any(|attr| attr.path().is_ident("protected"));
-        let is_readonly = attrs.iter().any(|attr| attr.path().is_ident("readonly"));
+        let attrs = parse_field_attrs(input)?;
 
         let name: Ident = input.parse()?;
         input.parse::<Token![:]>()?;
@@ -166,11 +302,44 @@ impl Parse for FieldDef {
         let raw_ty: Type = input.parse()?;
         let ty = TypeKind::try_from(&raw_ty)?;
 
+        let accessors = match (attrs.get, attrs.set) {
+            (Some(get), set) => {
+                if attrs.is_readonly && let Some(set) = &set {
+                    return Err(syn::Error::new_spanned(set, "#[readonly] fields have no setter"));
+                }
+                if let Some(rename) = &attrs.rename {
+                    return Err(syn::Error::new_spanned(
+                        rename,
+                        "`rename` names a data member, accessor fields have none",
+                    ));
+                }
+                if matches!(ty, TypeKind::Option(_)) {
+                    return Err(syn::Error::new_spanned(
+                        get,
+                        "Option fields cannot be bound through accessors",
+                    ));
+                }
+                Some(FieldAccessors {
+                    get: get.value(),
+                    set: set.map(|s| s.value()),
+                })
+            }
+            (None, Some(set)) => {
+                return Err(syn::Error::new_spanned(set, "`set` needs a matching `get`"));
+            }
+            (None, None) => None,
+        };
+        // a getter without a setter is read-only
+        let is_readonly = attrs.is_readonly || accessors.as_ref().is_some_and(|a| a.set.is_none());
+        let cpp_name = attrs.rename.map_or_else(|| name.to_string(), |r| r.value());
+
         Ok(FieldDef {
             name,
+            cpp_name,
             ty,
-            is_protected,
+            is_protected: attrs.is_protected,
             is_readonly,
+            accessors,
         })
     }
 }
@@ -185,7 +354,9 @@ impl Parse for ImplDef {
 
         let mut methods = Vec::new();
         while !content.is_empty() {
-            methods.push(content.parse()?);
+            let mut method: MethodDef = content.parse()?;
+            method.resolve_self(&target.to_string());
+            methods.push(method);
         }
 
         Ok(ImplDef { target, methods })
@@ -209,9 +380,13 @@ fn parse_args_and_kind(input: ParseStream) -> Result<(Vec<Arg>, MethodKind)> {
             input.parse::<Token![,]>()?;
         }
     } else if input.peek(Token![self]) || input.peek(Token![mut]) {
-        return Err(
-            input.error("Pass-by-value `self` is not supported. Use `&self` or `&mut self`")
-        );
+        input.parse::<Option<Token![mut]>>()?;
+        input.parse::<Token![self]>()?;
+        kind = MethodKind::Consuming;
+
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
     }
 
     let args_list = input.parse_terminated(
@@ -220,12 +395,43 @@ fn parse_args_and_kind(input: ParseStream) -> Result<(Vec<Arg>, MethodKind)> {
             stream.parse::<Token![:]>()?;
             let raw_ty: Type = stream.parse()?;
             let ty = TypeKind::try_from(&raw_ty)?;
-            Ok(Arg { name, ty })
+            let default = if stream.peek(Token![=]) {
+                stream.parse::<Token![=]>()?;
+                let expr: syn::Expr = stream.parse()?;
+                if !matches!(
+                    ty,
+                    TypeKind::Primitive(_) | TypeKind::String | TypeKind::Object(_)
+                ) {
+                    return Err(syn::Error::new_spanned(
+                        expr,
+                        "Default values are only supported for by-value arguments",
+                    ));
+                }
+                Some(expr)
+            } else {
+                None
+            };
+            Ok(Arg {
+                name,
+                ty,
+                default,
+                is_out: false,
+            })
         },
         Token![,],
     )?;
 
-    Ok((args_list.into_iter().collect(), kind))
+    let args: Vec<Arg> = args_list.into_iter().collect();
+    if let Some(first_default) = args.iter().position(|a| a.default.is_some())
+        && let Some(arg) = args[first_default..].iter().find(|a| a.default.is_none())
+    {
+        return Err(syn::Error::new_spanned(
+            &arg.name,
+            "Arguments after a defaulted argument must also have defaults",
+        ));
+    }
+
+    Ok((args, kind))
 }
 
 impl Parse for MethodDef {
@@ -240,10 +446,13 @@ impl Parse for MethodDef {
 
         let args_content;
         parenthesized!(args_content in input);
-        let (args, kind) = parse_args_and_kind(&args_content)?;
+        let (mut args, kind) = parse_args_and_kind(&args_content)?;
 
-        if let Some(yield_ty) = iter_ty_kind {
-            return IterDef::parse_rest(input, rust_name, args, kind, yield_ty)
+        let out_names = parse_out_attr(&attrs)?;
+        mark_out_args(&mut args, &out_names)?;
+
+        if let Some(iter_attr) = iter_ty_kind {
+            return IterDef::parse_rest(input, rust_name, args, kind, is_protected, iter_attr)
                 .map(MethodDef::Iter);
         }
 
@@ -255,9 +464,24 @@ impl Parse for MethodDef {
             None
         };
 
-        let is_return_self = matches!(&ret_ty_kind, Some(TypeKind::Object(s)) if s == "Self");
-        if is_return_self {
-            return CtorDef::parse_rest(input, rust_name, args, kind).map(MethodDef::Ctor);
+        let is_self = |ty: &TypeKind| matches!(ty, TypeKind::Object(s) if s == "Self");
+        let on_failure = match &ret_ty_kind {
+            Some(ty) if is_self(ty) => Some(CtorFailure::Abort),
+            Some(TypeKind::Result(inner)) if is_self(inner) => Some(CtorFailure::Result),
+            Some(TypeKind::Option(inner)) if is_self(inner) => Some(CtorFailure::Option),
+            _ => None,
+        };
+        if let Some(on_failure) = on_failure
+            && kind == MethodKind::Static
+        {
+            if let Some(name) = out_names.first() {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "#[out] is not supported on constructors",
+                ));
+            }
+            return CtorDef::parse_rest(input, rust_name, args, kind, is_protected, on_failure)
+                .map(MethodDef::Ctor);
         }
 
         FnDef::parse_rest_with_ret(input, rust_name, args, kind, is_protected, ret_ty_kind)
@@ -271,34 +495,106 @@ impl IterDef {
         rust_name: Ident,
         args: Vec<Arg>,
         kind: MethodKind,
-        yield_ty: TypeKind,
+        is_protected: bool,
+        iter_attr: IterAttr,
     ) -> Result<Self> {
+        let IterAttr {
+            item: yield_ty,
+            is_drain,
+            double_ended,
+            range,
+            begin_end,
+        } = iter_attr;
+
         if !args.is_empty() {
             return Err(input.error("Iterator methods cannot take arguments"));
         }
 
-        let is_iter_mut = match kind {
-            MethodKind::Mutable => true,
-            MethodKind::Const => false,
-            MethodKind::Static => return Err(input.error("Iterator must take &self or &mut self")),
+        let mode = match (&yield_ty, is_drain) {
+            (TypeKind::Reference { .. }, true) => {
+                return Err(input.error("#[iter(drain)] yields elements by value, not references"));
+            }
+            (TypeKind::Reference { is_mut: false, .. }, false) => IterMode::Ref,
+            (TypeKind::Reference { is_mut: true, .. }, false) => IterMode::Mut,
+            (_, true) => IterMode::Drain,
+            // objects may still turn out to be enums, checked once those are known
+            (TypeKind::Primitive(_) | TypeKind::String | TypeKind::Object(_), false) => {
+                IterMode::Copy
+            }
+            (_, false) => {
+                return Err(input.error(
+                    "By-value object items move out of the container; use Item = &T or #[iter(drain)]",
+                ));
+            }
         };
 
-        let is_item_mut = matches!(yield_ty, TypeKind::Reference { is_mut: true, .. });
+        if let TypeKind::Reference { inner, .. } = &yield_ty
+            && !inner.is_object_value()
+        {
+            return Err(input.error(
+                "Borrowing iterators must yield references to objects; use Item = T to copy values",
+            ));
+        }
 
-        if is_item_mut && !is_iter_mut {
-            return Err(
-                input.error("Iterator producing mutable references (&mut T) must take &mut self")
-            );
+        match (mode, kind) {
+            (IterMode::Ref | IterMode::Copy, MethodKind::Const)
+            | (IterMode::Mut | IterMode::Drain, MethodKind::Mutable) => {}
+            (IterMode::Ref, _) => return Err(input.error("Iterators over &T must take &self")),
+            (IterMode::Copy, _) => {
+                return Err(input.error("Iterators copying items out must take &self"));
+            }
+            (IterMode::Mut, _) => {
+                return Err(input.error(
+                    "Iterator producing mutable references (&mut T) must take &mut self",
+                ));
+            }
+            (IterMode::Drain, _) => {
+                return Err(input.error("#[iter(drain)] must take &mut self"));
+            }
         }
 
-        let cpp_name = parse_cpp_mapping_str(input, &rust_name)?;
+        // `fn kids(&self) = children;` iterates over `obj.children()`
+        let mapped = if input.peek(Token![=]) {
+            Some(parse_cpp_mapping_str(input, &rust_name)?)
+        } else {
+            None
+        };
         input.parse::<Token![;]>()?;
 
+        let mut iter_range = IterRange::default();
+        match (mapped, range) {
+            (Some(_), Some(_)) => {
+                return Err(input.error("Use either `= name` or #[iter(range = ...)], not both"));
+            }
+            (Some(name), None) => {
+                iter_range.expr = format!("obj.{}()", name);
+                iter_range.member = Some(name.to_string());
+            }
+            (None, Some((expr, member))) => {
+                iter_range.expr = expr;
+                iter_range.member = member;
+            }
+            (None, None) => {}
+        }
+        if let Some((begin, end)) = begin_end {
+            iter_range.begin = begin;
+            iter_range.end = end;
+        }
+        // the exposer re-exports the member the range reads, or begin / end
+        if is_protected && iter_range.member.is_none() && iter_range.expr != "obj" {
+            return Err(syn::Error::new_spanned(
+                &rust_name,
+                "#[protected] iterators need a range starting with a member, e.g. \"obj.items()\"",
+            ));
+        }
+
         Ok(IterDef {
             rust_name,
             yield_ty,
-            is_iter_mut,
-            cpp_name,
+            range: iter_range,
+            mode,
+            double_ended,
+            is_protected,
         })
     }
 }
@@ -309,6 +605,8 @@ impl CtorDef {
         rust_name: Ident,
         args: Vec<Arg>,
         kind: MethodKind,
+        is_protected: bool,
+        on_failure: CtorFailure,
     ) -> Result<Self> {
         if kind != MethodKind::Static {
             return Err(input.error("Constructors must be static (no self)"));
@@ -327,6 +625,8 @@ impl CtorDef {
             args,
             cpp_name,
             is_user_defined: is_user,
+            is_protected,
+            on_failure,
         })
     }
 }
@@ -340,6 +640,13 @@ impl FnDef {
         is_protected: bool,
         ret_ty: Option<TypeKind>,
     ) -> Result<Self> {
+        if kind == MethodKind::Consuming && ret_ty.as_ref().is_some_and(|r| r.returns_reference()) {
+            return Err(input.error("Methods taking `self` by value cannot return references"));
+        }
+        if matches!(ret_ty, Some(TypeKind::Result(_))) {
+            return Err(input.error("Result is only supported as `-> Result<Self>` on constructors"));
+        }
+
         let cpp_name = parse_cpp_mapping_str(input, &rust_name)?;
         input.parse::<Token![;]>()?;
 
@@ -354,26 +661,214 @@ impl FnDef {
     }
 }
 
-fn parse_iter_attr(attrs: &[Attribute]) -> Result<Option<TypeKind>> {
+/// Everything `#[iter(...)]` can say about an iterator method.
+struct IterAttr {
+    item: TypeKind,
+    is_drain: bool,
+    double_ended: bool,
+    /// The range expression and the class member it reads, if any.
+    range: Option<(String, Option<String>)>,
+    begin_end: Option<(String, String)>,
+}
+
+fn parse_iter_attr(attrs: &[Attribute]) -> Result<Option<IterAttr>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("iter")) else {
+        return Ok(None);
+    };
+
+    let mut item = None;
+    let mut is_drain = false;
+    let mut double_ended = false;
+    let mut range = None;
+    let (mut begin, mut end) = (None, None);
+    attr.parse_nested_meta(|meta| {
+        let set_range = |range: &mut Option<_>, expr: String, member: Option<String>| {
+            if range.replace((expr, member)).is_some() {
+                return Err(meta.error("Only one of `range` and `field` can be given"));
+            }
+            Ok(())
+        };
+
+        if meta.path.is_ident("Item") {
+            let ty: Type = meta.value()?.parse()?;
+            item = Some(TypeKind::try_from(&ty)?);
+        } else if meta.path.is_ident("drain") {
+            is_drain = true;
+        } else if meta.path.is_ident("double_ended") {
+            double_ended = true;
+        } else if meta.path.is_ident("range") {
+            let value = meta.value()?;
+            if value.peek(LitStr) {
+                let expr = value.parse::<LitStr>()?.value();
+                let member = leading_member(&expr);
+                set_range(&mut range, expr, member)?;
+            } else {
+                let method = value.parse::<Ident>()?.to_string();
+                set_range(&mut range, format!("obj.{}()", method), Some(method))?;
+            }
+        } else if meta.path.is_ident("field") {
+            let field: Ident = meta.value()?.parse()?;
+            set_range(&mut range, format!("obj.{}", field), Some(field.to_string()))?;
+        } else if meta.path.is_ident("begin") {
+            begin = Some(meta.value()?.parse::<Ident>()?.to_string());
+        } else if meta.path.is_ident("end") {
+            end = Some(meta.value()?.parse::<Ident>()?.to_string());
+        } else {
+            return Err(meta.error(
+                "Expected `Item`, `drain`, `double_ended`, `range`, `field`, `begin` or `end`",
+            ));
+        }
+        Ok(())
+    })?;
+
+    let Some(item) = item else {
+        return Err(syn::Error::new_spanned(attr, "Missing Item in #[iter]"));
+    };
+    let begin_end = match (begin, end) {
+        (Some(b), Some(e)) => Some((b, e)),
+        (None, None) => None,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`begin` and `end` must be given together",
+            ));
+        }
+    };
+
+    Ok(Some(IterAttr {
+        item,
+        is_drain,
+        double_ended,
+        range,
+        begin_end,
+    }))
+}
+
+/// The member a range expression such as `obj.items(true)` starts with.
+fn leading_member(expr: &str) -> Option<String> {
+    let rest = expr.trim_start().strip_prefix("obj.")?;
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(rest.len());
+    let member = &rest[..end];
+    syn::parse_str::<Ident>(member).ok().map(|_| member.to_string())
+}
+
+fn parse_out_attr(attrs: &[Attribute]) -> Result<Vec<Ident>> {
+    let mut names = Vec::new();
     for attr in attrs {
-        if !attr.path().is_ident("iter") {
+        if !attr.path().is_ident("out") {
             continue;
         }
-        let nested = attr.parse_args_with(
-            syn::punctuated::Punctuated::<syn::Meta, Token![,]>::parse_terminated,
-        )?;
+        let nested = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+        names.extend(nested);
+    }
+    Ok(names)
+}
 
-        for meta in nested {
-            if let syn::Meta::NameValue(nv) = meta
-                && nv.path.is_ident("Item")
-            {
-                let ty: syn::Type = syn::parse2(nv.value.to_token_stream())?;
-                return TypeKind::try_from(&ty).map(Some);
+fn mark_out_args(args: &mut [Arg], out_names: &[Ident]) -> Result<()> {
+    for name in out_names {
+        let Some(arg) = args.iter_mut().find(|a| a.name == *name) else {
+            return Err(syn::Error::new_spanned(name, "No argument with this name"));
+        };
+        let is_supported = matches!(
+            &arg.ty,
+            TypeKind::Reference { inner, is_mut: true }
+                if matches!(**inner, TypeKind::Primitive(_) | TypeKind::String)
+        );
+        if !is_supported {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Out arguments must be &mut to a primitive or String",
+            ));
+        }
+        if arg.default.is_some() {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Out arguments cannot have default values",
+            ));
+        }
+        arg.is_out = true;
+    }
+    Ok(())
+}
+
+fn parse_derive_attr(attrs: &[Attribute]) -> Result<Vec<Ident>> {
+    let mut derives = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("derive") {
+            continue;
+        }
+        let nested = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+        for derive in nested {
+            let is_known = matches!(
+                derive.to_string().as_str(),
+                "Clone" | "Debug" | "Init" | "Mirror" | "Serialize" | "Deserialize"
+            );
+            if !is_known {
+                return Err(syn::Error::new_spanned(
+                    derive,
+                    "Expected `Clone`, `Debug`, `Init`, `Mirror`, `Serialize` or `Deserialize`",
+                ));
             }
+            derives.push(derive);
         }
-        return Err(syn::Error::new_spanned(attr, "Missing Item in #[iter]"));
     }
-    Ok(None)
+    Ok(derives)
+}
+
+fn parse_contiguous_attr(attrs: &[Attribute]) -> Result<Option<ContiguousDef>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("contiguous")) else {
+        return Ok(None);
+    };
+
+    let mut elem = None;
+    let mut resize = false;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("elem") {
+            let ty: Type = meta.value()?.parse()?;
+            match TypeKind::try_from(&ty)? {
+                kind @ TypeKind::Primitive(_) => elem = Some(kind),
+                _ => return Err(syn::Error::new_spanned(ty, "Contiguous elements must be primitives")),
+            }
+            Ok(())
+        } else if meta.path.is_ident("resize") {
+            resize = true;
+            Ok(())
+        } else {
+            Err(meta.error("Expected `elem = T` or `resize`"))
+        }
+    })?;
+
+    match elem {
+        Some(elem) => Ok(Some(ContiguousDef { elem, resize })),
+        None => Err(syn::Error::new_spanned(attr, "Expected #[contiguous(elem = T)]")),
+    }
+}
+
+/// A bare `#[name]` marker; arguments are rejected rather than ignored.
+fn parse_flag_attr(attrs: &[Attribute], name: &str) -> Result<bool> {
+    for attr in attrs {
+        if attr.path().is_ident(name) {
+            attr.meta.require_path_only()?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn parse_debug_attr(attrs: &[Attribute]) -> Result<bool> {
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+        let option: Ident = attr.parse_args()?;
+        if option != "protected" {
+            return Err(syn::Error::new_spanned(option, "Expected #[debug(protected)]"));
+        }
+        return Ok(true);
+    }
+    Ok(false)
 }
 
 fn parse_cpp_mapping_str(input: ParseStream, default: &Ident) -> Result<String> {
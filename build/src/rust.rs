@@ -1,25 +1,39 @@
 use crate::ast::*;
-use crate::ffi::{generate_ffi_block, generate_map_ffi, generate_vec_ffi};
-use crate::wrapper::{generate_map_wrappers, generate_vec_wrappers, generate_wrapper_block};
+use crate::ffi::{
+    generate_enum_ffi, generate_ffi_block, generate_map_ffi, generate_mirror_struct,
+    generate_vec_ffi,
+};
+use crate::wrapper::{
+    generate_enum_wrapper, generate_map_wrappers, generate_vec_wrappers, generate_wrapper_block,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
 
 pub fn generate_rust(bind_context: &BindContext) -> TokenStream {
+    let mut shared_items = Vec::new();
     let mut extern_cpp_items = Vec::new();
     let mut rust_wrapper_items = Vec::new();
 
+    for def in &bind_context.enums {
+        let (shared, extern_cpp) = generate_enum_ffi(def);
+        shared_items.push(shared);
+        extern_cpp_items.push(extern_cpp);
+        rust_wrapper_items.push(generate_enum_wrapper(def));
+    }
+
     for class_name_str in &bind_context.class_names_order {
         let class = bind_context.models.get(class_name_str).unwrap();
 
+        shared_items.push(generate_mirror_struct(class));
         extern_cpp_items.push(generate_ffi_block(class));
 
-        rust_wrapper_items.push(generate_wrapper_block(class));
+        rust_wrapper_items.push(generate_wrapper_block(class, bind_context));
     }
 
-    extern_cpp_items.push(generate_vec_ffi(&bind_context.vec_defs));
-    rust_wrapper_items.push(generate_vec_wrappers(&bind_context.vec_defs));
-    extern_cpp_items.push(generate_map_ffi(&bind_context.map_defs));
-    rust_wrapper_items.push(generate_map_wrappers(&bind_context.map_defs));
+    extern_cpp_items.push(generate_vec_ffi(bind_context));
+    rust_wrapper_items.push(generate_vec_wrappers(bind_context));
+    extern_cpp_items.push(generate_map_ffi(bind_context));
+    rust_wrapper_items.push(generate_map_wrappers(bind_context));
 
     let includes = &bind_context.includes;
 
@@ -72,17 +86,6 @@ pub fn generate_rust(bind_context: &BindContext) -> TokenStream {
             }
         }
 
-        impl<'a, T: justcxx::CppClass, M: justcxx::Mode, S: justcxx::Storage<T>> std::fmt::Debug
-            for CppObject<'a, T, M, S>
-        {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                unsafe {
-                    let ptr = S::as_ptr(&self.inner);
-                    write!(f, "CppObject({:p})", ptr)
-                }
-            }
-        }
-
         impl<'a, T: justcxx::CppClass, M: justcxx::Mode, S: justcxx::Storage<T>> PartialEq
             for CppObject<'a, T, M, S>
         {
@@ -105,7 +108,10 @@ pub fn generate_rust(bind_context: &BindContext) -> TokenStream {
         pub struct CppMapPtr<K,V>(pub std::marker::PhantomData<(K,V)>);
 
         #[cxx::bridge]
+        #[allow(clippy::too_many_arguments)]
         mod ffi {
+            #(#shared_items)*
+
             unsafe extern "C++" {
                 #(include!(#includes);)*
                 include!("justcxx.hh");
@@ -1,7 +1,6 @@
 use crate::ast::*;
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
-use std::collections::HashSet;
 
 struct MethodGroups {
     universal_common: Vec<TokenStream>,
@@ -11,7 +10,16 @@ struct MethodGroups {
     aux: Option<TokenStream>,
 }
 
-pub fn generate_wrapper_block(class: &ClassModel) -> TokenStream {
+#[derive(Default)]
+struct MethodOutputs {
+    common: Vec<TokenStream>,
+    muts: Vec<TokenStream>,
+    statics: Vec<TokenStream>,
+    owned: Vec<TokenStream>,
+    aux: Option<TokenStream>,
+}
+
+pub fn generate_wrapper_block(class: &ClassModel, bind_context: &BindContext) -> TokenStream {
     let class_name = &class.name;
     let _owned_name = format_ident!("{}Owned", class_name);
 
@@ -36,6 +44,7 @@ pub fn generate_wrapper_block(class: &ClassModel) -> TokenStream {
     let mut ref_const_methods = Vec::new();
     let mut ref_mut_methods = Vec::new();
     let mut static_methods = Vec::new();
+    let mut owned_methods = Vec::new();
     let mut aux_items = Vec::new();
 
     for field in &class.fields {
@@ -56,15 +65,56 @@ pub fn generate_wrapper_block(class: &ClassModel) -> TokenStream {
     }
 
     for method in &class.methods {
-        let (commons, muts, static_muts, aux) = generate_wrapper_method(class, method);
-        common_methods.extend(commons);
+        let MethodOutputs {
+            common,
+            muts,
+            statics,
+            owned,
+            aux,
+        } = generate_wrapper_method(class, method);
+        common_methods.extend(common);
         mut_methods.extend(muts);
-        static_methods.extend(static_muts);
+        static_methods.extend(statics);
+        owned_methods.extend(owned);
         if let Some(a) = aux {
             aux_items.push(a);
         }
     }
 
+    let tag = quote! { #class_name };
+    if let Some(contiguous) = &class.contiguous {
+        aux_items.push(generate_contiguous_impl(class_name, contiguous));
+    }
+    if class.has_derive("Clone") {
+        let clone_fn = get_clone_fn_name(&TypeKind::Object(class_name.to_string()));
+        aux_items.push(generate_clone_impl(&tag, &clone_fn));
+    }
+    if class.has_derive("Init") {
+        aux_items.push(generate_init_struct(class));
+    }
+    if class.has_derive("Mirror") {
+        aux_items.push(generate_mirror_impl(class_name));
+    }
+    aux_items.push(generate_reflect_impl(class, bind_context));
+    if class.has_derive("Serialize") {
+        aux_items.push(generate_serialize_impl(class));
+    }
+    if class.has_derive("Deserialize") {
+        aux_items.push(generate_deserialize_impl(class, bind_context));
+    }
+    let debug_body = if class.contiguous.is_some() {
+        quote! { f.debug_list().entries(self.as_slice()).finish() }
+    } else {
+        generate_class_debug_body(class)
+    };
+    aux_items.push(generate_debug_impl(&tag, debug_body));
+    if class.is_display {
+        aux_items.push(generate_display_impl(class_name));
+    }
+    if class.is_from_str {
+        aux_items.push(generate_from_str_impl(class_name));
+    }
+
     let static_impl = if !static_methods.is_empty() {
         quote! {
             impl #class_name {
@@ -113,6 +163,16 @@ pub fn generate_wrapper_block(class: &ClassModel) -> TokenStream {
         quote! {}
     };
 
+    let owned_impl = if !owned_methods.is_empty() {
+        quote! {
+            impl CppObject<'static, #class_name, justcxx::Mut, justcxx::Owned> {
+                #(#owned_methods)*
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     quote! {
         #tag_def
         #type_aliases
@@ -121,477 +181,2563 @@ pub fn generate_wrapper_block(class: &ClassModel) -> TokenStream {
         #ref_const_impl
         #ref_mut_impl
         #mut_impl
+        #owned_impl
         #(#aux_items)*
     }
 }
 
-pub fn generate_vec_wrappers(vec_defs: &HashSet<TypeKind>) -> TokenStream {
-    let mut items: Vec<TokenStream> = Vec::new();
-
-    let mut sorted_defs: Vec<&TypeKind> = vec_defs.iter().collect();
-    sorted_defs.sort_by(|a, b| {
-        let name_a = a.get_flat_name();
-        let name_b = b.get_flat_name();
-        name_a.cmp(&name_b)
-    });
-
-    for def in sorted_defs {
-        if let TypeKind::Vector { inner, is_ptr: _ } = def {
-            let elem_ident = inner.to_rust_tag();
-            let ffi_type = def.to_ffi_type_name_only();
-            let ffi_type_str = def.get_flat_name();
-            let rust_tag = def.to_rust_tag();
-            let new_fn = format_ident!("make_{}_new", ffi_type_str);
-
-            items.push(quote! {
-                impl justcxx::CppClass for #rust_tag {
-                    type FfiType = ffi::#ffi_type;
-                }
-                impl justcxx::CppTypeAliases for #rust_tag {
-                    type Owned = CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>;
-                    type Ref<'a> = CppObject<'a, #rust_tag, justcxx::Const, justcxx::Ref>;
-                    type Mut<'a> = CppObject<'a, #rust_tag, justcxx::Mut, justcxx::Ref>;
-                }
-
-                impl #rust_tag {
-                    pub fn new() -> justcxx::CppOwned<#rust_tag> {
-                        unsafe {
-                            let ptr = ffi::#new_fn();
-                            CppObject { inner: ptr, _marker: std::marker::PhantomData}
-                        }
-                    }
+fn generate_clone_impl(rust_tag: &TokenStream, clone_fn: &Ident) -> TokenStream {
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
+            pub fn to_owned(&self) -> justcxx::CppOwned<#rust_tag> {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    let unique_ptr = ffi::#clone_fn(&*ptr);
+                    CppObject { inner: unique_ptr, _marker: std::marker::PhantomData }
                 }
-            });
+            }
+        }
 
-            match **inner {
-                TypeKind::String => generate_vec_string(&ffi_type_str, &rust_tag, &mut items),
-                TypeKind::Primitive(_) => {
-                    generate_vec_primitive(&ffi_type_str, &elem_ident, &rust_tag, &mut items)
-                }
-                TypeKind::Object(_) | TypeKind::Map { .. } | TypeKind::Vector { .. } => {
-                    generate_vec_obj(&ffi_type_str, &elem_ident, &rust_tag, &mut items)
-                }
-                _ => {}
+        impl Clone for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            fn clone(&self) -> Self {
+                self.to_owned()
             }
         }
     }
-    quote! { #(#items)* }
 }
 
-fn generate_vec_primitive(
-    type_prefix: &str,
-    elem_ident: &TokenStream,
-    rust_tag: &TokenStream,
-    items: &mut Vec<TokenStream>,
-) {
-    let len_fn = format_ident!("{}_len", type_prefix);
-    let get_fn = format_ident!("{}_get", type_prefix);
-    let get_mut_fn = format_ident!("{}_get_mut", type_prefix);
-    let push_fn = format_ident!("{}_push", type_prefix);
-    let slice_fn = format_ident!("{}_as_slice", type_prefix);
-    let mut_slice_fn = format_ident!("{}_as_mut_slice", type_prefix);
+/// `#[derive(Init)]`: a plain Rust struct with one public field per bound
+/// field, turned into the C++ object by aggregate initialization.
+fn generate_init_struct(class: &ClassModel) -> TokenStream {
+    let class_name = &class.name;
+    let init_name = get_init_struct_name(class_name);
+    let from_init_fn = get_from_init_fn_name(class_name);
 
-    let common_methods = quote! {
-        pub fn len(&self) -> usize {
-            unsafe {
-                let ptr = self.as_ptr();
-                ffi::#len_fn(&*ptr)
+    let mut fields = Vec::new();
+    let mut args = Vec::new();
+    for field in &class.fields {
+        let name = &field.name;
+        let ty = match field.init_arg().unwrap() {
+            InitArg::Value => {
+                args.push(match field.ty {
+                    TypeKind::String => quote! { &init.#name },
+                    _ => quote! { init.#name },
+                });
+                field.ty.to_rust_wrapper_ret_type(None)
+            }
+            InitArg::Optional => {
+                args.push(quote! { init.#name.is_some() });
+                args.push(match &field.ty {
+                    TypeKind::Option(inner) if **inner == TypeKind::String => {
+                        quote! { init.#name.as_deref().unwrap_or_default() }
+                    }
+                    _ => quote! { init.#name.unwrap_or_default() },
+                });
+                field.ty.to_rust_wrapper_ret_type(None)
+            }
+            InitArg::Object => {
+                args.push(quote! { init.#name.map_or_else(cxx::UniquePtr::null, |v| v.inner) });
+                let obj = match &field.ty {
+                    TypeKind::Option(inner) => inner.as_ref(),
+                    ty => ty,
+                };
+                let t = obj.to_rust_wrapper_arg_type();
+                quote! { Option<#t> }
             }
+        };
+        fields.push(quote! { pub #name: #ty });
+    }
+
+    let doc = format!(
+        "Fields of [`{}`] for [`{}::from_init`]; `None` objects are value-initialized.",
+        class_name, class_name
+    );
+    quote! {
+        #[doc = #doc]
+        #[derive(Default)]
+        pub struct #init_name {
+            #(#fields),*
         }
-        pub fn get(&self, index: usize) -> Option<#elem_ident> {
-            unsafe{
-                let ptr = self.as_ptr();
-                match ffi::#get_fn(&*ptr, index) {
-                    Ok(n) => Some(n),
-                    Err(_) => None,
+
+        impl #class_name {
+            pub fn from_init(init: #init_name) -> justcxx::CppOwned<#class_name> {
+                unsafe {
+                    CppObject {
+                        inner: ffi::#from_init_fn(#(#args),*),
+                        _marker: std::marker::PhantomData,
+                    }
                 }
             }
         }
-        pub fn as_slice(&self) -> &[#elem_ident] {
-            unsafe {
-                let ptr = self.as_ptr();
-                ffi::#slice_fn(&*ptr)
+    }
+}
+
+/// Declared enums are re-exported from the bridge and reflect as their
+/// variant names, falling back to the raw value for undeclared ones.
+pub fn generate_enum_wrapper(def: &EnumDef) -> TokenStream {
+    let name = &def.name;
+    let repr = &def.repr;
+    let idents = def.variants.iter().map(|v| &v.ident);
+    let names = def.variants.iter().map(|v| v.ident.to_string());
+    let variants = quote! { [#((#name::#idents, #names)),*] };
+    let first = &def.variants[0].ident;
+    quote! {
+        pub use ffi::#name;
+
+        impl Default for #name {
+            fn default() -> Self {
+                #name::#first
             }
         }
 
-        pub fn iter(&self) -> impl Iterator<Item = &#elem_ident>{
-            self.as_slice().iter()
+        impl justcxx::ToValue for #name {
+            fn to_value(&self) -> justcxx::Value {
+                match #variants.into_iter().find(|(v, _)| v == self) {
+                    Some((_, name)) => justcxx::Value::String(name.to_string()),
+                    None => justcxx::ToValue::to_value(&self.repr),
+                }
+            }
         }
-    };
 
-    let mut_methods = quote! {
-        pub fn get_mut(&mut self, index: usize) -> Option<&mut #elem_ident> {
-            unsafe{
-                let ptr = self.as_ptr();
-                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                match ffi::#get_mut_fn(pin_self, index) {
-                    Ok(ret) => Some(
-                        ret.get_unchecked_mut()
-                    ),
-                    Err(_) => None,
+        impl justcxx::FromValue for #name {
+            fn from_value(value: justcxx::Value) -> Option<Self> {
+                let variants = #variants;
+                match value {
+                    justcxx::Value::String(s) => {
+                        variants.into_iter().find(|(_, name)| *name == s).map(|(v, _)| v)
+                    }
+                    value => {
+                        let repr = <#repr as justcxx::FromValue>::from_value(value)?;
+                        variants.into_iter().find(|(v, _)| v.repr == repr).map(|(v, _)| v)
+                    }
                 }
             }
         }
+    }
+}
 
-        pub fn push(&mut self, val: #elem_ident) {
-            unsafe {
-                let ptr = self.as_ptr();
-                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                ffi::#push_fn(pin_self, val);
+/// `#[derive(Mirror)]`: every mirrored field in one call each way.
+fn generate_mirror_impl(class_name: &Ident) -> TokenStream {
+    let data_name = get_mirror_data_name(class_name);
+    let (snapshot_fn, apply_fn) = get_mirror_fn_names(class_name);
+    quote! {
+        pub use ffi::#data_name;
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> CppObject<'a, #class_name, M, S> {
+            pub fn snapshot(&self) -> #data_name {
+                unsafe { ffi::#snapshot_fn(&*self.as_ptr()) }
             }
         }
 
-        pub fn as_mut_slice(&self) -> &mut [#elem_ident] {
-            unsafe {
-                let ptr = self.as_ptr();
-                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                ffi::#mut_slice_fn(pin_self)
+        impl<'a, S: justcxx::Storage<#class_name>> CppObject<'a, #class_name, justcxx::Mut, S> {
+            pub fn apply(&mut self, data: &#data_name) {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#apply_fn(std::pin::Pin::new_unchecked(&mut *ptr), data);
+                }
             }
         }
-        pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut #elem_ident>{
-            self.as_mut_slice().iter_mut()
-        }
-    };
+    }
+}
 
-    items.push(quote! {
-        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
-            #common_methods
-        }
-        impl<'a, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, justcxx::Mut, S> {
-            #mut_methods
+/// `FIELDS` on the tag, plus `Reflect`, `ToValue` and `FromValue` through
+/// the field getters and setters.
+fn generate_reflect_impl(class: &ClassModel, bind_context: &BindContext) -> TokenStream {
+    let class_name = &class.name;
+    // fields without a getter have no value to reflect
+    let fields: Vec<&FieldDef> = class.fields.iter().filter(|f| f.has_getter()).collect();
+
+    let infos = fields.iter().map(|field| {
+        let name_str = field.name.to_string();
+        let ty_str = field.ty.describe();
+        let readonly = !field.has_setter();
+        let protected = field.is_protected;
+        quote! {
+            justcxx::FieldInfo {
+                name: #name_str,
+                ty: #ty_str,
+                readonly: #readonly,
+                protected: #protected,
+            }
         }
     });
-}
-
-fn generate_vec_string(type_prefix: &str, rust_tag: &TokenStream, items: &mut Vec<TokenStream>) {
-    let len_fn = format_ident!("{}_len", type_prefix);
-    let set_fn = format_ident!("{}_set", type_prefix);
-    let get_fn = format_ident!("{}_get", type_prefix);
-    let push_fn = format_ident!("{}_push", type_prefix);
 
-    let common_methods = quote! {
-        pub fn len(&self) -> usize {
-            unsafe {
-                let ptr = self.as_ptr();
+    let get_arms = fields.iter().map(|field| {
+        let name = &field.name;
+        let name_str = name.to_string();
+        quote! { #name_str => Some(justcxx::ToValue::to_value(&self.#name())), }
+    });
 
-                ffi::#len_fn(&*ptr)
+    let set_arms = fields.iter().map(|field| {
+        let name_str = field.name.to_string();
+        let set_name = field.get_wrapper_set_name();
+        let mismatch = quote! { .ok_or(justcxx::FieldError::TypeMismatch)? };
+        let body = match &field.ty {
+            _ if !field.has_setter() => quote! { Err(justcxx::FieldError::ReadOnly) },
+            TypeKind::Object(_) if field.has_mut_getter() => {
+                let name = &field.name;
+                quote! { justcxx::Reflect::set_fields(&mut self.as_mut().#name(), value) }
             }
-        }
-        pub unsafe fn get(&self, index: usize) -> Option<String> {
-            unsafe{
-                let ptr = self.as_ptr();
-                match ffi::#get_fn(&*ptr, index) {
-                    Ok(s) => Some(s),
-                    Err(_) => None,
+            TypeKind::Option(inner) => {
+                let clear_name = field.get_wrapper_clear_name();
+                let set = if bind_context.is_constructible(inner) {
+                    let arg = from_value_arg(inner, quote! { value }, &mismatch);
+                    quote! { self.as_mut().#set_name(#arg); Ok(()) }
+                } else {
+                    quote! { Err(justcxx::FieldError::TypeMismatch) }
+                };
+                quote! {
+                    if value == justcxx::Value::Null {
+                        self.as_mut().#clear_name();
+                        Ok(())
+                    } else {
+                        #set
+                    }
                 }
             }
-        }
+            ty if bind_context.is_constructible(ty) => {
+                let arg = from_value_arg(ty, quote! { value }, &mismatch);
+                quote! {{ self.as_mut().#set_name(#arg); Ok(()) }}
+            }
+            _ => quote! { Err(justcxx::FieldError::TypeMismatch) },
+        };
+        quote! { #name_str => #body, }
+    });
 
-        pub fn iter(&self) -> impl Iterator<Item = String> {
-             let this = self.as_ref();
-            (0..this.len()).map(move |i| unsafe{this.get(i).unwrap()})
+    let public_entries = fields.iter().filter(|f| !f.is_protected).map(|field| {
+        let name = &field.name;
+        let name_str = name.to_string();
+        quote! { (#name_str.to_string(), justcxx::ToValue::to_value(&this.#name())) }
+    });
+    let to_value_body = if class.contiguous.is_some() {
+        quote! { justcxx::ToValue::to_value(self.as_slice()) }
+    } else {
+        quote! {
+            let this = self.as_ref();
+            justcxx::Value::Object(vec![#(#public_entries),*])
         }
     };
 
-    let mut_methods = quote! {
-        pub fn push(&mut self, val: &str) {
-            unsafe {
-                let ptr = self.as_ptr();
-                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                ffi::#push_fn(pin_self, val);
+    let from_value_impl = if class.has_default_new() {
+        quote! {
+            impl justcxx::FromValue for CppObject<'static, #class_name, justcxx::Mut, justcxx::Owned> {
+                fn from_value(value: justcxx::Value) -> Option<Self> {
+                    let mut obj = #class_name::new();
+                    justcxx::Reflect::set_fields(&mut obj, value).ok()?;
+                    Some(obj)
+                }
             }
         }
+    } else {
+        quote! {}
+    };
 
-        pub fn set(&mut self,index: usize, val: &str) {
-            if index >= self.len() {
-                panic!("index out of bounds: the len is {} but the index is {}", self.len(), index);
+    quote! {
+        impl #class_name {
+            pub const FIELDS: &'static [justcxx::FieldInfo] = &[#(#infos),*];
+        }
+
+        impl<'a> justcxx::Reflect for CppObject<'a, #class_name, justcxx::Const, justcxx::Ref> {
+            fn fields(&self) -> &'static [justcxx::FieldInfo] {
+                #class_name::FIELDS
             }
-            unsafe {
-                let ptr = self.as_ptr();
-                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                ffi::#set_fn(pin_self,index, val);
+
+            fn get_field(&self, name: &str) -> Option<justcxx::Value> {
+                match name {
+                    #(#get_arms)*
+                    _ => None,
+                }
+            }
+
+            fn set_field(
+                &mut self,
+                name: &str,
+                _value: justcxx::Value,
+            ) -> Result<(), justcxx::FieldError> {
+                if #class_name::FIELDS.iter().any(|f| f.name == name) {
+                    Err(justcxx::FieldError::ReadOnly)
+                } else {
+                    Err(justcxx::FieldError::NotFound)
+                }
             }
         }
-    };
 
-    items.push(quote! {
-        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>>
-            CppObject<'a, #rust_tag, M, S>
+        impl<'a, S: justcxx::Storage<#class_name>> justcxx::Reflect
+            for CppObject<'a, #class_name, justcxx::Mut, S>
         {
-            #common_methods
+            fn fields(&self) -> &'static [justcxx::FieldInfo] {
+                #class_name::FIELDS
+            }
+
+            fn get_field(&self, name: &str) -> Option<justcxx::Value> {
+                justcxx::Reflect::get_field(&self.as_ref(), name)
+            }
+
+            fn set_field(
+                &mut self,
+                name: &str,
+                value: justcxx::Value,
+            ) -> Result<(), justcxx::FieldError> {
+                match name {
+                    #(#set_arms)*
+                    _ => Err(justcxx::FieldError::NotFound),
+                }
+            }
         }
-        impl<'a, S: justcxx::Storage<#rust_tag>>
-            CppObject<'a, #rust_tag, justcxx::Mut, S>
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> justcxx::ToValue
+            for CppObject<'a, #class_name, M, S>
         {
-            #mut_methods
+            fn to_value(&self) -> justcxx::Value {
+                #to_value_body
+            }
         }
 
-    });
+        #from_value_impl
+    }
 }
 
-fn generate_vec_obj(
-    type_prefix: &str,
-    elem_ident: &TokenStream,
-    rust_tag: &TokenStream,
-    items: &mut Vec<TokenStream>,
-) {
-    let len_fn = format_ident!("{}_len", type_prefix);
-    let get_fn = format_ident!("{}_get", type_prefix);
-    let get_mut_fn = format_ident!("{}_get_mut", type_prefix);
-    let push_fn = format_ident!("{}_push", type_prefix);
-    let common_methods = quote! {
-        pub fn len(&self) -> usize {
-            unsafe {
-                let ptr = self.as_ptr();
+/// Converts `value` into the argument a setter, `push` or `insert` takes;
+/// `on_fail` handles a value of the wrong type.
+fn from_value_arg(ty: &TypeKind, value: TokenStream, on_fail: &TokenStream) -> TokenStream {
+    match ty {
+        TypeKind::String => {
+            quote! { &<String as justcxx::FromValue>::from_value(#value)#on_fail }
+        }
+        TypeKind::Object(_) => {
+            let tag = ty.to_rust_tag();
+            quote! { <justcxx::CppOwned<#tag> as justcxx::FromValue>::from_value(#value)#on_fail }
+        }
+        _ => quote! { justcxx::FromValue::from_value(#value)#on_fail },
+    }
+}
 
-                ffi::#len_fn(&*ptr)
+/// `#[derive(Serialize)]`: a struct of every public field, read through the getters.
+fn generate_serialize_impl(class: &ClassModel) -> TokenStream {
+    let class_name = &class.name;
+    let class_name_str = class_name.to_string();
+    let fields: Vec<_> = class.fields.iter().filter(|f| !f.is_protected).collect();
+    let len = fields.len();
+    let entries = fields.iter().map(|field| {
+        let name = &field.name;
+        let name_str = name.to_string();
+        quote! { state.serialize_field(#name_str, &this.#name())?; }
+    });
+
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> justcxx::serde::Serialize
+            for CppObject<'a, #class_name, M, S>
+        {
+            fn serialize<Ser: justcxx::serde::Serializer>(
+                &self,
+                serializer: Ser,
+            ) -> Result<Ser::Ok, Ser::Error> {
+                use justcxx::serde::ser::SerializeStruct;
+                let this = self.as_ref();
+                let mut state = serializer.serialize_struct(#class_name_str, #len)?;
+                #(#entries)*
+                state.end()
             }
         }
-        pub fn get(&self, index: usize) -> Option<justcxx::CppRef<'_, #elem_ident>> {
+    }
+}
+
+/// `#[derive(Deserialize)]`: a `new()` object with every writable field set
+/// from the input; unknown and read-only fields are skipped. Sequences are
+/// read in the order `Serialize` writes the fields, as formats without field
+/// names (bincode, postcard) hand structs over that way.
+fn generate_deserialize_impl(class: &ClassModel, bind_context: &BindContext) -> TokenStream {
+    let class_name = &class.name;
+    let class_name_str = class_name.to_string();
+    let expecting = format!("struct {}", class_name);
+    let fields: Vec<_> = class.fields.iter().filter(|f| !f.is_protected).collect();
+    let names: Vec<_> = fields.iter().map(|f| f.name.to_string()).collect();
+
+    // `read(ty)` yields the next value, `ty` being a turbofish or empty
+    let assign = |field: &FieldDef, read: &dyn Fn(TokenStream) -> TokenStream| {
+        let set_name = field.get_wrapper_set_name();
+        match &field.ty {
+            ty if !field.has_setter() => {
+                let val = if bind_context.is_deserializable(ty) {
+                    let val_ty = serde_value_ty(ty);
+                    read(quote! { ::<#val_ty> })
+                } else {
+                    read(quote! { ::<justcxx::serde::de::IgnoredAny> })
+                };
+                quote! { #val; }
+            }
+            TypeKind::String => {
+                let val = read(quote! { ::<String> });
+                quote! { obj.as_mut().#set_name(&#val); }
+            }
+            TypeKind::Option(inner) => {
+                let clear_name = field.get_wrapper_clear_name();
+                let val_ty = serde_value_ty(&field.ty);
+                let val = read(quote! { ::<#val_ty> });
+                let inner_val = match **inner {
+                    TypeKind::String => quote! { &val },
+                    _ => quote! { val },
+                };
+                quote! {
+                    match #val {
+                        Some(val) => obj.as_mut().#set_name(#inner_val),
+                        None => obj.as_mut().#clear_name(),
+                    }
+                }
+            }
+            _ => {
+                let val = read(quote! {});
+                quote! { obj.as_mut().#set_name(#val); }
+            }
+        }
+    };
+
+    let arms = fields
+        .iter()
+        .zip(&names)
+        .filter(|(field, _)| field.has_setter())
+        .map(|(field, name_str)| {
+            let body = assign(field, &|ty| quote! { map.next_value #ty ()? });
+            quote! { #name_str => { #body } }
+        });
+    let seq_arg = if fields.is_empty() {
+        quote! { _seq }
+    } else {
+        quote! { mut seq }
+    };
+    let steps = fields.iter().enumerate().map(|(i, field)| {
+        assign(field, &|ty| {
+            quote! {
+                match seq.next_element #ty ()? {
+                    Some(val) => val,
+                    None => return Err(justcxx::serde::de::Error::invalid_length(#i, &self)),
+                }
+            }
+        })
+    });
+
+    quote! {
+        impl<'de> justcxx::serde::Deserialize<'de>
+            for CppObject<'static, #class_name, justcxx::Mut, justcxx::Owned>
+        {
+            fn deserialize<De: justcxx::serde::Deserializer<'de>>(
+                deserializer: De,
+            ) -> Result<Self, De::Error> {
+                struct Visitor;
+
+                impl<'de> justcxx::serde::de::Visitor<'de> for Visitor {
+                    type Value = justcxx::CppOwned<#class_name>;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+
+                    fn visit_map<A: justcxx::serde::de::MapAccess<'de>>(
+                        self,
+                        mut map: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        let obj = #class_name::new();
+                        while let Some(key) = map.next_key::<String>()? {
+                            match key.as_str() {
+                                #(#arms)*
+                                _ => {
+                                    map.next_value::<justcxx::serde::de::IgnoredAny>()?;
+                                }
+                            }
+                        }
+                        Ok(obj)
+                    }
+
+                    fn visit_seq<A: justcxx::serde::de::SeqAccess<'de>>(
+                        self,
+                        #seq_arg: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        let obj = #class_name::new();
+                        #(#steps)*
+                        Ok(obj)
+                    }
+                }
+
+                deserializer.deserialize_struct(#class_name_str, &[#(#names),*], Visitor)
+            }
+        }
+    }
+}
+
+/// The owned type a field value deserializes into.
+fn serde_value_ty(ty: &TypeKind) -> TokenStream {
+    match ty {
+        TypeKind::Object(_) | TypeKind::Vector { .. } | TypeKind::Map { .. } => {
+            let tag = ty.to_rust_tag();
+            quote! { justcxx::CppOwned<#tag> }
+        }
+        TypeKind::Option(inner) => {
+            let inner = serde_value_ty(inner);
+            quote! { Option<#inner> }
+        }
+        _ => ty.to_rust_wrapper_ret_type(None),
+    }
+}
+
+fn generate_container_to_value_impl(rust_tag: &TokenStream, body: TokenStream) -> TokenStream {
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> justcxx::ToValue
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn to_value(&self) -> justcxx::Value {
+                justcxx::Value::#body
+            }
+        }
+    }
+}
+
+/// Containers write out as sequences and maps of their elements.
+fn generate_container_serialize_impl(rust_tag: &TokenStream, collect: TokenStream) -> TokenStream {
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> justcxx::serde::Serialize
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn serialize<Ser: justcxx::serde::Serializer>(
+                &self,
+                serializer: Ser,
+            ) -> Result<Ser::Ok, Ser::Error> {
+                serializer.#collect(self.iter())
+            }
+        }
+    }
+}
+
+/// Containers read back element by element into a `new()` container.
+fn generate_container_deserialize_impl(
+    rust_tag: &TokenStream,
+    expecting: &str,
+    visit: TokenStream,
+    deserialize_fn: TokenStream,
+) -> TokenStream {
+    quote! {
+        impl<'de> justcxx::serde::Deserialize<'de>
+            for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>
+        {
+            fn deserialize<De: justcxx::serde::Deserializer<'de>>(
+                deserializer: De,
+            ) -> Result<Self, De::Error> {
+                struct Visitor;
+
+                impl<'de> justcxx::serde::de::Visitor<'de> for Visitor {
+                    type Value = justcxx::CppOwned<#rust_tag>;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+
+                    #visit
+                }
+
+                deserializer.#deserialize_fn(Visitor)
+            }
+        }
+    }
+}
+
+fn generate_debug_impl(rust_tag: &TokenStream, body: TokenStream) -> TokenStream {
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> std::fmt::Debug
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                #body
+            }
+        }
+    }
+}
+
+/// Slice access for `#[contiguous]` classes, modelled on the primitive vectors.
+fn generate_contiguous_impl(class_name: &Ident, contiguous: &ContiguousDef) -> TokenStream {
+    let elem = contiguous.elem.to_rust_tag();
+    let slice_fn = format_ident!("{}_as_slice", class_name);
+    let mut_slice_fn = format_ident!("{}_as_mut_slice", class_name);
+
+    let resize = if contiguous.resize {
+        let resize_fn = format_ident!("{}_slice_resize", class_name);
+        quote! {
+            pub fn resize(&mut self, new_len: usize) {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#resize_fn(std::pin::Pin::new_unchecked(&mut *ptr), new_len);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> CppObject<'a, #class_name, M, S> {
+            pub fn as_slice(&self) -> &[#elem] {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#slice_fn(&*ptr)
+                }
+            }
+
+            pub fn len(&self) -> usize {
+                self.as_slice().len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            pub fn iter(&self) -> std::slice::Iter<'_, #elem> {
+                self.as_slice().iter()
+            }
+        }
+
+        impl<'a, S: justcxx::Storage<#class_name>> CppObject<'a, #class_name, justcxx::Mut, S> {
+            pub fn as_mut_slice(&mut self) -> &mut [#elem] {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#mut_slice_fn(std::pin::Pin::new_unchecked(&mut *ptr))
+                }
+            }
+
+            pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, #elem> {
+                self.as_mut_slice().iter_mut()
+            }
+
+            #resize
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> justcxx::CppContainer
+            for CppObject<'a, #class_name, M, S>
+        {
+            fn len(&self) -> usize {
+                self.len()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> AsRef<[#elem]>
+            for CppObject<'a, #class_name, M, S>
+        {
+            fn as_ref(&self) -> &[#elem] {
+                self.as_slice()
+            }
+        }
+
+        impl<'a, S: justcxx::Storage<#class_name>> AsMut<[#elem]>
+            for CppObject<'a, #class_name, justcxx::Mut, S>
+        {
+            fn as_mut(&mut self) -> &mut [#elem] {
+                self.as_mut_slice()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>, I: std::slice::SliceIndex<[#elem]>>
+            std::ops::Index<I> for CppObject<'a, #class_name, M, S>
+        {
+            type Output = I::Output;
+            fn index(&self, index: I) -> &Self::Output {
+                &self.as_slice()[index]
+            }
+        }
+
+        impl<'a, S: justcxx::Storage<#class_name>, I: std::slice::SliceIndex<[#elem]>>
+            std::ops::IndexMut<I> for CppObject<'a, #class_name, justcxx::Mut, S>
+        {
+            fn index_mut(&mut self, index: I) -> &mut Self::Output {
+                &mut self.as_mut_slice()[index]
+            }
+        }
+    }
+}
+
+fn generate_class_debug_body(class: &ClassModel) -> TokenStream {
+    if !class.has_derive("Debug") {
+        return quote! { write!(f, "CppObject({:p})", self.as_ptr()) };
+    }
+
+    let class_name_str = class.name.to_string();
+    let fields = class
+        .fields
+        .iter()
+        .filter(|field| !field.is_protected || class.debug_protected)
+        .map(|field| {
+            let name = &field.name;
+            let name_str = name.to_string();
+            quote! { .field(#name_str, &this.#name()) }
+        });
+
+    quote! {
+        let this = self.as_ref();
+        f.debug_struct(#class_name_str)
+            #(#fields)*
+            .finish()
+    }
+}
+
+fn generate_display_impl(class_name: &Ident) -> TokenStream {
+    let to_string_fn = get_to_string_fn_name(class_name);
+    quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#class_name>> std::fmt::Display
+            for CppObject<'a, #class_name, M, S>
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let s = unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#to_string_fn(&*ptr)
+                };
+                f.pad(&s)
+            }
+        }
+    }
+}
+
+fn generate_from_str_impl(class_name: &Ident) -> TokenStream {
+    let from_str_fn = get_from_str_fn_name(class_name);
+    quote! {
+        impl std::str::FromStr for CppObject<'static, #class_name, justcxx::Mut, justcxx::Owned> {
+            type Err = cxx::Exception;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let unique_ptr = ffi::#from_str_fn(s)?;
+                Ok(CppObject { inner: unique_ptr, _marker: std::marker::PhantomData })
+            }
+        }
+    }
+}
+
+pub fn generate_vec_wrappers(bind_context: &BindContext) -> TokenStream {
+    let mut items: Vec<TokenStream> = Vec::new();
+
+    let mut sorted_defs: Vec<&TypeKind> = bind_context.vec_defs.iter().collect();
+    sorted_defs.sort_by(|a, b| {
+        let name_a = a.get_flat_name();
+        let name_b = b.get_flat_name();
+        name_a.cmp(&name_b)
+    });
+
+    for def in sorted_defs {
+        if let TypeKind::Vector { inner, is_ptr: _ } = def {
+            let elem_ident = inner.to_rust_tag();
+            let ffi_type = def.to_ffi_type_name_only();
+            let ffi_type_str = def.get_flat_name();
+            let rust_tag = def.to_rust_tag();
+            let new_fn = format_ident!("make_{}_new", ffi_type_str);
+
+            items.push(quote! {
+                impl justcxx::CppClass for #rust_tag {
+                    type FfiType = ffi::#ffi_type;
+                }
+                impl justcxx::CppTypeAliases for #rust_tag {
+                    type Owned = CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>;
+                    type Ref<'a> = CppObject<'a, #rust_tag, justcxx::Const, justcxx::Ref>;
+                    type Mut<'a> = CppObject<'a, #rust_tag, justcxx::Mut, justcxx::Ref>;
+                }
+
+                impl #rust_tag {
+                    pub fn new() -> justcxx::CppOwned<#rust_tag> {
+                        unsafe {
+                            let ptr = ffi::#new_fn();
+                            CppObject { inner: ptr, _marker: std::marker::PhantomData}
+                        }
+                    }
+                }
+            });
+
+            if bind_context.is_cloneable(def) {
+                items.push(generate_clone_impl(&rust_tag, &get_clone_fn_name(def)));
+            }
+            items.push(generate_debug_impl(
+                &rust_tag,
+                quote! { f.debug_list().entries(self.iter()).finish() },
+            ));
+            items.push(generate_container_to_value_impl(
+                &rust_tag,
+                quote! { List(self.iter().map(|v| justcxx::ToValue::to_value(&v)).collect()) },
+            ));
+            if bind_context.is_constructible(def) {
+                let val = from_value_arg(inner, quote! { item }, &quote! { ? });
+                items.push(quote! {
+                    impl justcxx::FromValue for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+                        fn from_value(value: justcxx::Value) -> Option<Self> {
+                            let justcxx::Value::List(items) = value else {
+                                return None;
+                            };
+                            let mut out = <#rust_tag>::new();
+                            for item in items {
+                                out.push(#val);
+                            }
+                            Some(out)
+                        }
+                    }
+                });
+            }
+            if bind_context.has_derive("Serialize") && bind_context.is_serializable(def) {
+                items.push(generate_container_serialize_impl(
+                    &rust_tag,
+                    quote! { collect_seq },
+                ));
+            }
+            if bind_context.has_derive("Deserialize") && bind_context.is_deserializable(def) {
+                let elem_ty = inner.to_rust_wrapper_ret_type(None);
+                let val = match **inner {
+                    TypeKind::String => quote! { &val },
+                    _ => quote! { val },
+                };
+                items.push(generate_container_deserialize_impl(
+                    &rust_tag,
+                    "a sequence",
+                    quote! {
+                        fn visit_seq<A: justcxx::serde::de::SeqAccess<'de>>(
+                            self,
+                            mut seq: A,
+                        ) -> Result<Self::Value, A::Error> {
+                            let mut out = <#rust_tag>::new();
+                            while let Some(val) = seq.next_element::<#elem_ty>()? {
+                                out.push(#val);
+                            }
+                            Ok(out)
+                        }
+                    },
+                    quote! { deserialize_seq },
+                ));
+            }
+
+            match **inner {
+                TypeKind::String => generate_vec_string(&ffi_type_str, &rust_tag, &mut items),
+                TypeKind::Primitive(_) if inner.is_bool() => {
+                    generate_vec_bool(&ffi_type_str, &rust_tag, &mut items)
+                }
+                TypeKind::Primitive(_) | TypeKind::Enum(_) => {
+                    generate_vec_primitive(&ffi_type_str, &elem_ident, &rust_tag, &mut items)
+                }
+                TypeKind::Object(_) | TypeKind::Map { .. } | TypeKind::Vector { .. } => {
+                    let is_cloneable = bind_context.is_cloneable(inner);
+                    generate_vec_obj(&ffi_type_str, &elem_ident, &rust_tag, is_cloneable, &mut items)
+                }
+                _ => {}
+            }
+        }
+    }
+    quote! { #(#items)* }
+}
+
+fn generate_vec_primitive(
+    type_prefix: &str,
+    elem_ident: &TokenStream,
+    rust_tag: &TokenStream,
+    items: &mut Vec<TokenStream>,
+) {
+    let len_fn = format_ident!("{}_len", type_prefix);
+    let get_fn = format_ident!("{}_get", type_prefix);
+    let get_mut_fn = format_ident!("{}_get_mut", type_prefix);
+    let push_fn = format_ident!("{}_push", type_prefix);
+    let slice_fn = format_ident!("{}_as_slice", type_prefix);
+    let mut_slice_fn = format_ident!("{}_as_mut_slice", type_prefix);
+    let extend_fn = format_ident!("{}_extend_from_slice", type_prefix);
+
+    let common_methods = quote! {
+        pub fn len(&self) -> usize {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#len_fn(&*ptr)
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+        pub fn get(&self, index: usize) -> Option<#elem_ident> {
             unsafe{
                 let ptr = self.as_ptr();
                 match ffi::#get_fn(&*ptr, index) {
-                    Ok(ret_ref) => {
-                        let ret_ptr = (ret_ref as *const _) as *mut _;
-                        Some(CppObject {
-                            inner: ret_ptr,
-                            _marker: std::marker::PhantomData
-                        })
-                    },
+                    Ok(n) => Some(n),
                     Err(_) => None,
                 }
             }
-
-        }
-
-        pub fn iter(&self) -> impl Iterator<Item = justcxx::CppRef<'_,#elem_ident>> + '_ {
-            (0..self.len()).map(move |i| self.get(i).unwrap())
+        }
+        pub fn as_slice(&self) -> &[#elem_ident] {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#slice_fn(&*ptr)
+            }
+        }
+
+        pub fn iter(&self) -> std::slice::Iter<'_, #elem_ident> {
+            self.as_slice().iter()
+        }
+
+        pub fn first(&self) -> Option<#elem_ident> {
+            self.get(0)
+        }
+
+        pub fn last(&self) -> Option<#elem_ident> {
+            self.len().checked_sub(1).and_then(|i| self.get(i))
+        }
+
+        pub fn to_vec(&self) -> Vec<#elem_ident> {
+            self.as_slice().to_vec()
+        }
+    };
+
+    let mut_methods = quote! {
+        pub fn get_mut(&mut self, index: usize) -> Option<&mut #elem_ident> {
+            unsafe{
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                match ffi::#get_mut_fn(pin_self, index) {
+                    Ok(ret) => Some(
+                        ret.get_unchecked_mut()
+                    ),
+                    Err(_) => None,
+                }
+            }
+        }
+
+        pub fn push(&mut self, val: #elem_ident) {
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#push_fn(pin_self, val);
+            }
+        }
+
+        pub fn as_mut_slice(&mut self) -> &mut [#elem_ident] {
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#mut_slice_fn(pin_self)
+            }
+        }
+        pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, #elem_ident> {
+            self.as_mut_slice().iter_mut()
+        }
+
+        pub fn extend_from_slice(&mut self, vals: &[#elem_ident]) {
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#extend_fn(pin_self, vals);
+            }
+        }
+
+        pub fn extend(&mut self, iter: impl IntoIterator<Item = #elem_ident>) {
+            let vals: Vec<#elem_ident> = iter.into_iter().collect();
+            self.extend_from_slice(&vals);
+        }
+
+        pub fn resize(&mut self, new_len: usize, value: #elem_ident) {
+            self.resize_with(new_len, || value);
+        }
+
+        pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> #elem_ident) {
+            let len = self.len();
+            if new_len <= len {
+                self.truncate(new_len);
+            } else {
+                self.reserve(new_len - len);
+                for _ in len..new_len {
+                    self.push(f());
+                }
+            }
+        }
+    };
+
+    items.push(quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
+            #common_methods
+        }
+        impl<'a, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, justcxx::Mut, S> {
+            #mut_methods
+        }
+
+        impl<'b, 'a, S: justcxx::Storage<#rust_tag>> Extend<&'b #elem_ident>
+            for CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            fn extend<I: IntoIterator<Item = &'b #elem_ident>>(&mut self, iter: I) {
+                self.extend(iter.into_iter().copied());
+            }
+        }
+
+        impl From<&[#elem_ident]> for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            fn from(vals: &[#elem_ident]) -> Self {
+                let mut vec = <#rust_tag>::new();
+                vec.extend_from_slice(vals);
+                vec
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>, I: std::slice::SliceIndex<[#elem_ident]>>
+            std::ops::Index<I> for CppObject<'a, #rust_tag, M, S>
+        {
+            type Output = I::Output;
+            fn index(&self, index: I) -> &Self::Output {
+                &self.as_slice()[index]
+            }
+        }
+
+        impl<'a, S: justcxx::Storage<#rust_tag>, I: std::slice::SliceIndex<[#elem_ident]>>
+            std::ops::IndexMut<I> for CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            fn index_mut(&mut self, index: I) -> &mut Self::Output {
+                &mut self.as_mut_slice()[index]
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> PartialEq<[#elem_ident]>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn eq(&self, other: &[#elem_ident]) -> bool {
+                self.as_slice() == other
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> PartialEq<Vec<#elem_ident>>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn eq(&self, other: &Vec<#elem_ident>) -> bool {
+                self.as_slice() == other.as_slice()
+            }
+        }
+
+        impl<'x, 'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x CppObject<'a, #rust_tag, M, S>
+        {
+            type Item = &'x #elem_ident;
+            type IntoIter = std::slice::Iter<'x, #elem_ident>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'x, 'a, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x mut CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            type Item = &'x mut #elem_ident;
+            type IntoIter = std::slice::IterMut<'x, #elem_ident>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+
+        impl<'a> IntoIterator for CppObject<'a, #rust_tag, justcxx::Const, justcxx::Ref> {
+            type Item = &'a #elem_ident;
+            type IntoIter = std::slice::Iter<'a, #elem_ident>;
+            fn into_iter(self) -> Self::IntoIter {
+                unsafe { ffi::#slice_fn(&*self.as_ptr()).iter() }
+            }
+        }
+
+        impl<'a> IntoIterator for CppObject<'a, #rust_tag, justcxx::Mut, justcxx::Ref> {
+            type Item = &'a mut #elem_ident;
+            type IntoIter = std::slice::IterMut<'a, #elem_ident>;
+            fn into_iter(self) -> Self::IntoIter {
+                unsafe {
+                    let pin_self = std::pin::Pin::new_unchecked(&mut *self.as_ptr());
+                    ffi::#mut_slice_fn(pin_self).iter_mut()
+                }
+            }
+        }
+    });
+    items.push(generate_vec_collect_impls(rust_tag, elem_ident, &quote! { vec.to_vec() }));
+
+    generate_vec_modify(
+        type_prefix,
+        rust_tag,
+        &VecElemTokens {
+            owned_ty: elem_ident.clone(),
+            arg_ty: elem_ident.clone(),
+            arg_conv: quote! { val },
+            ret_conv: quote! { ret },
+            retain_arg: quote! { &#elem_ident },
+            retain_conv: quote! { x },
+        },
+        items,
+    );
+}
+
+/// `std::vector<bool>` is bit-packed, so unlike other primitives it has no
+/// slice view; every element goes through `get`/`set`.
+fn generate_vec_bool(type_prefix: &str, rust_tag: &TokenStream, items: &mut Vec<TokenStream>) {
+    let len_fn = format_ident!("{}_len", type_prefix);
+    let get_fn = format_ident!("{}_get", type_prefix);
+    let set_fn = format_ident!("{}_set", type_prefix);
+    let push_fn = format_ident!("{}_push", type_prefix);
+    let extend_fn = format_ident!("{}_extend_from_slice", type_prefix);
+    let to_vec_fn = format_ident!("{}_to_vec", type_prefix);
+    let iter_struct_name = format_ident!("{}_Iter", type_prefix);
+    let ffi_type = format_ident!("{}", type_prefix);
+
+    let common_methods = quote! {
+        pub fn len(&self) -> usize {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#len_fn(&*ptr)
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        pub fn get(&self, index: usize) -> Option<bool> {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#get_fn(&*ptr, index).ok()
+            }
+        }
+
+        pub fn iter(&self) -> #iter_struct_name<'_> {
+            #iter_struct_name::new(self.as_ptr())
+        }
+
+        pub fn first(&self) -> Option<bool> {
+            self.get(0)
+        }
+
+        pub fn last(&self) -> Option<bool> {
+            self.len().checked_sub(1).and_then(|i| self.get(i))
+        }
+
+        pub fn to_vec(&self) -> Vec<bool> {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#to_vec_fn(&*ptr)
+            }
+        }
+    };
+
+    let mut_methods = quote! {
+        pub fn push(&mut self, val: bool) {
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#push_fn(pin_self, val);
+            }
+        }
+
+        pub fn set(&mut self, index: usize, val: bool) {
+            if index >= self.len() {
+                panic!("index out of bounds: the len is {} but the index is {}", self.len(), index);
+            }
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#set_fn(pin_self, index, val);
+            }
+        }
+
+        pub fn extend_from_slice(&mut self, vals: &[bool]) {
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#extend_fn(pin_self, vals);
+            }
+        }
+
+        pub fn extend(&mut self, iter: impl IntoIterator<Item = bool>) {
+            let vals: Vec<bool> = iter.into_iter().collect();
+            self.extend_from_slice(&vals);
+        }
+
+        pub fn resize(&mut self, new_len: usize, value: bool) {
+            self.resize_with(new_len, || value);
+        }
+
+        pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> bool) {
+            let len = self.len();
+            if new_len <= len {
+                self.truncate(new_len);
+            } else {
+                let vals: Vec<bool> = (len..new_len).map(|_| f()).collect();
+                self.extend_from_slice(&vals);
+            }
+        }
+    };
+
+    items.push(quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>>
+            CppObject<'a, #rust_tag, M, S>
+        {
+            #common_methods
+        }
+        impl<'a, S: justcxx::Storage<#rust_tag>>
+            CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            #mut_methods
+        }
+
+        pub struct #iter_struct_name<'a> {
+            vec: *mut ffi::#ffi_type,
+            front: usize,
+            back: usize,
+            _marker: std::marker::PhantomData<&'a ()>,
+        }
+
+        impl<'a> #iter_struct_name<'a> {
+            fn new(vec: *mut ffi::#ffi_type) -> Self {
+                let back = unsafe { ffi::#len_fn(&*vec) };
+                Self { vec, front: 0, back, _marker: std::marker::PhantomData }
+            }
+
+            fn at(&self, index: usize) -> bool {
+                unsafe { ffi::#get_fn(&*self.vec, index).unwrap() }
+            }
+        }
+
+        impl<'a> Iterator for #iter_struct_name<'a> {
+            type Item = bool;
+
+            fn next(&mut self) -> Option<bool> {
+                if self.front == self.back {
+                    return None;
+                }
+                self.front += 1;
+                Some(self.at(self.front - 1))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.back - self.front;
+                (len, Some(len))
+            }
+        }
+
+        impl<'a> DoubleEndedIterator for #iter_struct_name<'a> {
+            fn next_back(&mut self) -> Option<bool> {
+                if self.front == self.back {
+                    return None;
+                }
+                self.back -= 1;
+                Some(self.at(self.back))
+            }
+        }
+
+        impl<'a> ExactSizeIterator for #iter_struct_name<'a> {}
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> std::ops::Index<usize>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            type Output = bool;
+            fn index(&self, index: usize) -> &bool {
+                match self.get(index) {
+                    Some(true) => &true,
+                    Some(false) => &false,
+                    None => panic!(
+                        "index out of bounds: the len is {} but the index is {}",
+                        self.len(),
+                        index
+                    ),
+                }
+            }
+        }
+
+        impl<'b, 'a, S: justcxx::Storage<#rust_tag>> Extend<&'b bool>
+            for CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            fn extend<I: IntoIterator<Item = &'b bool>>(&mut self, iter: I) {
+                self.extend(iter.into_iter().copied());
+            }
+        }
+
+        impl From<&[bool]> for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            fn from(vals: &[bool]) -> Self {
+                let mut vec = <#rust_tag>::new();
+                vec.extend_from_slice(vals);
+                vec
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> PartialEq<[bool]>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn eq(&self, other: &[bool]) -> bool {
+                self.to_vec() == other
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> PartialEq<Vec<bool>>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn eq(&self, other: &Vec<bool>) -> bool {
+                *self == *other.as_slice()
+            }
+        }
+
+        impl<'x, 'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x CppObject<'a, #rust_tag, M, S>
+        {
+            type Item = bool;
+            type IntoIter = #iter_struct_name<'x>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> IntoIterator for CppObject<'a, #rust_tag, M, justcxx::Ref> {
+            type Item = bool;
+            type IntoIter = #iter_struct_name<'a>;
+            fn into_iter(self) -> Self::IntoIter {
+                #iter_struct_name::new(self.as_ptr())
+            }
+        }
+    });
+    items.push(generate_vec_collect_impls(rust_tag, &quote! { bool }, &quote! { vec.to_vec() }));
+
+    generate_vec_modify(
+        type_prefix,
+        rust_tag,
+        &VecElemTokens {
+            owned_ty: quote! { bool },
+            arg_ty: quote! { bool },
+            arg_conv: quote! { val },
+            ret_conv: quote! { ret },
+            retain_arg: quote! { &bool },
+            retain_conv: quote! { &x },
+        },
+        items,
+    );
+}
+
+fn generate_vec_string(type_prefix: &str, rust_tag: &TokenStream, items: &mut Vec<TokenStream>) {
+    let len_fn = format_ident!("{}_len", type_prefix);
+    let set_fn = format_ident!("{}_set", type_prefix);
+    let get_fn = format_ident!("{}_get", type_prefix);
+    let push_fn = format_ident!("{}_push", type_prefix);
+    let extend_fn = format_ident!("{}_extend_from_slice", type_prefix);
+    let to_vec_fn = format_ident!("{}_to_vec", type_prefix);
+
+    let common_methods = quote! {
+        pub fn len(&self) -> usize {
+            unsafe {
+                let ptr = self.as_ptr();
+
+                ffi::#len_fn(&*ptr)
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+        pub fn get(&self, index: usize) -> Option<String> {
+            unsafe{
+                let ptr = self.as_ptr();
+                match ffi::#get_fn(&*ptr, index) {
+                    Ok(s) => Some(s),
+                    Err(_) => None,
+                }
+            }
+        }
+
+        pub fn iter(&self) -> std::vec::IntoIter<String> {
+            self.to_vec().into_iter()
+        }
+
+        pub fn first(&self) -> Option<String> {
+            self.get(0)
+        }
+
+        pub fn last(&self) -> Option<String> {
+            self.len().checked_sub(1).and_then(|i| self.get(i))
+        }
+
+        pub fn to_vec(&self) -> Vec<String> {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#to_vec_fn(&*ptr)
+            }
+        }
+    };
+
+    let mut_methods = quote! {
+        pub fn push(&mut self, val: &str) {
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#push_fn(pin_self, val);
+            }
+        }
+
+        pub fn set(&mut self,index: usize, val: &str) {
+            if index >= self.len() {
+                panic!("index out of bounds: the len is {} but the index is {}", self.len(), index);
+            }
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#set_fn(pin_self,index, val);
+            }
+        }
+
+        pub fn extend_from_slice<T: AsRef<str>>(&mut self, vals: &[T]) {
+            let strs: Vec<&str> = vals.iter().map(AsRef::as_ref).collect();
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#extend_fn(pin_self, &strs);
+            }
+        }
+
+        pub fn extend<I: IntoIterator<Item = T>, T: AsRef<str>>(&mut self, iter: I) {
+            let vals: Vec<T> = iter.into_iter().collect();
+            self.extend_from_slice(&vals);
+        }
+
+        pub fn resize(&mut self, new_len: usize, value: &str) {
+            let len = self.len();
+            if new_len <= len {
+                self.truncate(new_len);
+            } else {
+                self.reserve(new_len - len);
+                for _ in len..new_len {
+                    self.push(value);
+                }
+            }
+        }
+
+        pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> String) {
+            let len = self.len();
+            if new_len <= len {
+                self.truncate(new_len);
+            } else {
+                self.reserve(new_len - len);
+                for _ in len..new_len {
+                    self.push(&f());
+                }
+            }
+        }
+    };
+
+    items.push(quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>>
+            CppObject<'a, #rust_tag, M, S>
+        {
+            #common_methods
+        }
+        impl<'a, S: justcxx::Storage<#rust_tag>>
+            CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            #mut_methods
+        }
+
+        impl<T: AsRef<str>> From<&[T]> for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            fn from(vals: &[T]) -> Self {
+                let mut vec = <#rust_tag>::new();
+                vec.extend_from_slice(vals);
+                vec
+            }
+        }
+
+        impl<'a, T: AsRef<str>, S: justcxx::Storage<#rust_tag>> Extend<T>
+            for CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+                self.extend(iter);
+            }
+        }
+
+        impl<T: AsRef<str>> FromIterator<T> for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+                let mut vec = <#rust_tag>::new();
+                vec.extend(iter);
+                vec
+            }
+        }
+
+        impl CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            pub fn into_vec(self) -> Vec<String> {
+                self.to_vec()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>, T: AsRef<str>> PartialEq<[T]>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn eq(&self, other: &[T]) -> bool {
+                let this = self.to_vec();
+                this.len() == other.len() && this.iter().zip(other).all(|(a, b)| a == b.as_ref())
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>, T: AsRef<str>> PartialEq<Vec<T>>
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn eq(&self, other: &Vec<T>) -> bool {
+                *self == *other.as_slice()
+            }
+        }
+
+        impl<'x, 'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x CppObject<'a, #rust_tag, M, S>
+        {
+            type Item = String;
+            type IntoIter = std::vec::IntoIter<String>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> IntoIterator for CppObject<'a, #rust_tag, M, justcxx::Ref> {
+            type Item = String;
+            type IntoIter = std::vec::IntoIter<String>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl IntoIterator for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            type Item = String;
+            type IntoIter = std::vec::IntoIter<String>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.into_vec().into_iter()
+            }
+        }
+    });
+
+    generate_vec_modify(
+        type_prefix,
+        rust_tag,
+        &VecElemTokens {
+            owned_ty: quote! { String },
+            arg_ty: quote! { &str },
+            arg_conv: quote! { val },
+            ret_conv: quote! { ret },
+            retain_arg: quote! { &str },
+            retain_conv: quote! { &x },
+        },
+        items,
+    );
+}
+
+fn generate_vec_obj(
+    type_prefix: &str,
+    elem_ident: &TokenStream,
+    rust_tag: &TokenStream,
+    is_cloneable: bool,
+    items: &mut Vec<TokenStream>,
+) {
+    let len_fn = format_ident!("{}_len", type_prefix);
+    let get_fn = format_ident!("{}_get", type_prefix);
+    let get_mut_fn = format_ident!("{}_get_mut", type_prefix);
+    let push_fn = format_ident!("{}_push", type_prefix);
+    let set_fn = format_ident!("{}_set", type_prefix);
+    let iter_struct_name = format_ident!("{}_Iter", type_prefix);
+    let ffi_type = format_ident!("{}", type_prefix);
+    // new elements are copies of `value`, so only copyable classes get it
+    let resize = if is_cloneable {
+        quote! {
+            pub fn resize(&mut self, new_len: usize, value: justcxx::CppRef<'_, #elem_ident>) {
+                self.resize_with(new_len, || value.to_owned());
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let common_methods = quote! {
+        pub fn len(&self) -> usize {
+            unsafe {
+                let ptr = self.as_ptr();
+
+                ffi::#len_fn(&*ptr)
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+        pub fn get(&self, index: usize) -> Option<justcxx::CppRef<'_, #elem_ident>> {
+            unsafe{
+                let ptr = self.as_ptr();
+                match ffi::#get_fn(&*ptr, index) {
+                    Ok(ret_ref) => {
+                        let ret_ptr = (ret_ref as *const _) as *mut _;
+                        Some(CppObject {
+                            inner: ret_ptr,
+                            _marker: std::marker::PhantomData
+                        })
+                    },
+                    Err(_) => None,
+                }
+            }
+
+        }
+
+        pub fn iter(&self) -> #iter_struct_name<'_, justcxx::Const> {
+            #iter_struct_name::new(self.as_ptr())
+        }
+
+        pub fn first(&self) -> Option<justcxx::CppRef<'_, #elem_ident>> {
+            self.get(0)
+        }
+
+        pub fn last(&self) -> Option<justcxx::CppRef<'_, #elem_ident>> {
+            self.len().checked_sub(1).and_then(|i| self.get(i))
+        }
+    };
+
+    let mut_methods = quote! {
+        pub fn get_mut(&mut self, index: usize) -> Option<justcxx::CppMut<'_, #elem_ident>>{
+            unsafe{
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                match ffi::#get_mut_fn(pin_self, index) {
+                    Ok(ret_ref) => {
+                        let ret_ptr = ret_ref.get_unchecked_mut() as *mut _;
+                        Some(CppObject {
+                            inner: ret_ptr,
+                            _marker: std::marker::PhantomData
+                        })
+                    },
+                    Err(_) => None,
+                }
+            }
+        }
+
+        pub fn push(&mut self, val: justcxx::CppOwned<#elem_ident>){
+            unsafe{
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#push_fn(pin_self, val.inner);
+            }
+        }
+
+        pub fn set(&mut self, index: usize, val: justcxx::CppOwned<#elem_ident>) {
+            if index >= self.len() {
+                panic!("index out of bounds: the len is {} but the index is {}", self.len(), index);
+            }
+            unsafe {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                ffi::#set_fn(pin_self, index, val.inner);
+            }
+        }
+
+        pub fn extend(&mut self, iter: impl IntoIterator<Item = justcxx::CppOwned<#elem_ident>>) {
+            let iter = iter.into_iter();
+            self.reserve(iter.size_hint().0);
+            for val in iter {
+                self.push(val);
+            }
+        }
+
+        #resize
+
+        pub fn resize_with(
+            &mut self,
+            new_len: usize,
+            mut f: impl FnMut() -> justcxx::CppOwned<#elem_ident>,
+        ) {
+            let len = self.len();
+            if new_len <= len {
+                self.truncate(new_len);
+            } else {
+                self.reserve(new_len - len);
+                for _ in len..new_len {
+                    self.push(f());
+                }
+            }
+        }
+
+        pub fn iter_mut(&mut self) -> #iter_struct_name<'_, justcxx::Mut> {
+            #iter_struct_name::new(self.as_ptr())
+        }
+    };
+
+    items.push(quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>>
+            CppObject<'a, #rust_tag, M, S>
+        {
+            #common_methods
+        }
+        impl<'a, S: justcxx::Storage<#rust_tag>>
+            CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            #mut_methods
+        }
+    });
+    items.push(quote! {
+        pub struct #iter_struct_name<'a, M: justcxx::Mode> {
+            vec: *mut ffi::#ffi_type,
+            front: usize,
+            back: usize,
+            _marker: std::marker::PhantomData<(&'a (), M)>,
+        }
+
+        impl<'a, M: justcxx::Mode> #iter_struct_name<'a, M> {
+            fn new(vec: *mut ffi::#ffi_type) -> Self {
+                let back = unsafe { ffi::#len_fn(&*vec) };
+                Self { vec, front: 0, back, _marker: std::marker::PhantomData }
+            }
+
+            fn at(&self, index: usize) -> CppObject<'a, #elem_ident, M, justcxx::Ref> {
+                unsafe {
+                    let ret_ref = ffi::#get_fn(&*self.vec, index).unwrap();
+                    CppObject {
+                        inner: (ret_ref as *const _) as *mut _,
+                        _marker: std::marker::PhantomData
+                    }
+                }
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> Iterator for #iter_struct_name<'a, M> {
+            type Item = CppObject<'a, #elem_ident, M, justcxx::Ref>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.front == self.back {
+                    return None;
+                }
+                self.front += 1;
+                Some(self.at(self.front - 1))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.back - self.front;
+                (len, Some(len))
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> DoubleEndedIterator for #iter_struct_name<'a, M> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                if self.front == self.back {
+                    return None;
+                }
+                self.back -= 1;
+                Some(self.at(self.back))
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> ExactSizeIterator for #iter_struct_name<'a, M> {}
+
+        impl<'x, 'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x CppObject<'a, #rust_tag, M, S>
+        {
+            type Item = justcxx::CppRef<'x, #elem_ident>;
+            type IntoIter = #iter_struct_name<'x, justcxx::Const>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'x, 'a, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x mut CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            type Item = justcxx::CppMut<'x, #elem_ident>;
+            type IntoIter = #iter_struct_name<'x, justcxx::Mut>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> IntoIterator for CppObject<'a, #rust_tag, M, justcxx::Ref> {
+            type Item = CppObject<'a, #elem_ident, M, justcxx::Ref>;
+            type IntoIter = #iter_struct_name<'a, M>;
+            fn into_iter(self) -> Self::IntoIter {
+                #iter_struct_name::new(self.as_ptr())
+            }
+        }
+    });
+    items.push(generate_vec_collect_impls(
+        rust_tag,
+        &quote! { justcxx::CppOwned<#elem_ident> },
+        &quote! {
+            let mut out = Vec::with_capacity(vec.len());
+            while let Some(val) = vec.pop() {
+                out.push(val);
+            }
+            out.reverse();
+            out
+        },
+    ));
+
+    generate_vec_modify(
+        type_prefix,
+        rust_tag,
+        &VecElemTokens {
+            owned_ty: quote! { justcxx::CppOwned<#elem_ident> },
+            arg_ty: quote! { justcxx::CppOwned<#elem_ident> },
+            arg_conv: quote! { val.inner },
+            ret_conv: quote! {
+                CppObject {
+                    inner: ret,
+                    _marker: std::marker::PhantomData
+                }
+            },
+            retain_arg: quote! { justcxx::CppRef<'_, #elem_ident> },
+            retain_conv: quote! { x },
+        },
+        items,
+    );
+}
+
+/// `Extend`, `FromIterator` and `into_vec` on top of the inherent `extend`.
+/// `into_vec` moves every element out of `vec`.
+fn generate_vec_collect_impls(
+    rust_tag: &TokenStream,
+    owned_ty: &TokenStream,
+    into_vec_body: &TokenStream,
+) -> TokenStream {
+    quote! {
+        impl<'a, S: justcxx::Storage<#rust_tag>> Extend<#owned_ty>
+            for CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            fn extend<I: IntoIterator<Item = #owned_ty>>(&mut self, iter: I) {
+                self.extend(iter);
+            }
+        }
+
+        impl FromIterator<#owned_ty> for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            fn from_iter<I: IntoIterator<Item = #owned_ty>>(iter: I) -> Self {
+                let mut vec = <#rust_tag>::new();
+                vec.extend(iter);
+                vec
+            }
+        }
+
+        impl CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            pub fn into_vec(self) -> Vec<#owned_ty> {
+                let mut vec = self;
+                #into_vec_body
+            }
+        }
+
+        impl IntoIterator for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+            type Item = #owned_ty;
+            type IntoIter = std::vec::IntoIter<#owned_ty>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.into_vec().into_iter()
+            }
+        }
+    }
+}
+
+/// How a vector element moves in and out of the vector by value.
+struct VecElemTokens {
+    /// Type returned by `pop`, `remove` and `swap_remove`.
+    owned_ty: TokenStream,
+    /// Type accepted by `insert`.
+    arg_ty: TokenStream,
+    /// Converts `val` into its FFI form.
+    arg_conv: TokenStream,
+    /// Converts the FFI value `ret` into `owned_ty`.
+    ret_conv: TokenStream,
+    /// Argument type of the `retain` predicate.
+    retain_arg: TokenStream,
+    /// Converts the iterator item `x` into `retain_arg`.
+    retain_conv: TokenStream,
+}
+
+fn generate_vec_modify(
+    type_prefix: &str,
+    rust_tag: &TokenStream,
+    elem: &VecElemTokens,
+    items: &mut Vec<TokenStream>,
+) {
+    let clear_fn = format_ident!("{}_clear", type_prefix);
+    let truncate_fn = format_ident!("{}_truncate", type_prefix);
+    let reserve_fn = format_ident!("{}_reserve", type_prefix);
+    let capacity_fn = format_ident!("{}_capacity", type_prefix);
+    let swap_fn = format_ident!("{}_swap", type_prefix);
+    let pop_fn = format_ident!("{}_pop", type_prefix);
+    let remove_fn = format_ident!("{}_remove", type_prefix);
+    let swap_remove_fn = format_ident!("{}_swap_remove", type_prefix);
+    let insert_fn = format_ident!("{}_insert", type_prefix);
+    let retain_fn = format_ident!("{}_retain_mask", type_prefix);
+
+    let VecElemTokens {
+        owned_ty,
+        arg_ty,
+        arg_conv,
+        ret_conv,
+        retain_arg,
+        retain_conv,
+    } = elem;
+
+    items.push(quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> justcxx::CppContainer
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn len(&self) -> usize {
+                self.len()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
+            pub fn capacity(&self) -> usize {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#capacity_fn(&*ptr)
+                }
+            }
+        }
+
+        impl<'a, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, justcxx::Mut, S> {
+            pub fn clear(&mut self) {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#clear_fn(std::pin::Pin::new_unchecked(&mut *ptr));
+                }
+            }
+
+            pub fn truncate(&mut self, len: usize) {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#truncate_fn(std::pin::Pin::new_unchecked(&mut *ptr), len);
+                }
+            }
+
+            pub fn reserve(&mut self, additional: usize) {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#reserve_fn(std::pin::Pin::new_unchecked(&mut *ptr), additional);
+                }
+            }
+
+            pub fn swap(&mut self, a: usize, b: usize) {
+                let len = self.len();
+                if a >= len || b >= len {
+                    panic!("index out of bounds: the len is {} but the index is {}", len, a.max(b));
+                }
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#swap_fn(std::pin::Pin::new_unchecked(&mut *ptr), a, b);
+                }
+            }
+
+            pub fn pop(&mut self) -> Option<#owned_ty> {
+                if self.is_empty() {
+                    return None;
+                }
+                unsafe {
+                    let ptr = self.as_ptr();
+                    let ret = ffi::#pop_fn(std::pin::Pin::new_unchecked(&mut *ptr));
+                    Some(#ret_conv)
+                }
+            }
+
+            pub fn remove(&mut self, index: usize) -> #owned_ty {
+                let len = self.len();
+                if index >= len {
+                    panic!("removal index (is {}) should be < len (is {})", index, len);
+                }
+                unsafe {
+                    let ptr = self.as_ptr();
+                    let ret = ffi::#remove_fn(std::pin::Pin::new_unchecked(&mut *ptr), index);
+                    #ret_conv
+                }
+            }
+
+            pub fn swap_remove(&mut self, index: usize) -> #owned_ty {
+                let len = self.len();
+                if index >= len {
+                    panic!("swap_remove index (is {}) should be < len (is {})", index, len);
+                }
+                unsafe {
+                    let ptr = self.as_ptr();
+                    let ret = ffi::#swap_remove_fn(std::pin::Pin::new_unchecked(&mut *ptr), index);
+                    #ret_conv
+                }
+            }
+
+            pub fn insert(&mut self, index: usize, val: #arg_ty) {
+                let len = self.len();
+                if index > len {
+                    panic!("insertion index (is {}) should be <= len (is {})", index, len);
+                }
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#insert_fn(std::pin::Pin::new_unchecked(&mut *ptr), index, #arg_conv);
+                }
+            }
+
+            pub fn retain(&mut self, mut f: impl FnMut(#retain_arg) -> bool) {
+                let keep: Vec<bool> = self.iter().map(|x| f(#retain_conv)).collect();
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#retain_fn(std::pin::Pin::new_unchecked(&mut *ptr), &keep);
+                }
+            }
+        }
+    });
+}
+
+pub fn generate_map_wrappers(bind_context: &BindContext) -> TokenStream {
+    let mut items: Vec<TokenStream> = Vec::new();
+
+    let mut sorted_defs: Vec<&TypeKind> = bind_context.map_defs.iter().collect();
+    sorted_defs.sort_by_key(|a| a.get_flat_name());
+
+    for def in sorted_defs {
+        if let TypeKind::Map {
+            key,
+            value,
+            is_val_ptr: _,
+        } = def
+        {
+            let rust_tag = def.to_rust_tag();
+            let ffi_type = def.to_ffi_type_name_only();
+            let flat_name = def.get_flat_name();
+            let new_fn = format_ident!("make_{}_new", &flat_name);
+
+            items.push(quote! {
+                impl justcxx::CppClass for #rust_tag {
+                    type FfiType = ffi::#ffi_type;
+                }
+                impl justcxx::CppTypeAliases for #rust_tag {
+                    type Owned = CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>;
+                    type Ref<'a> = CppObject<'a, #rust_tag, justcxx::Const, justcxx::Ref>;
+                    type Mut<'a> = CppObject<'a, #rust_tag, justcxx::Mut, justcxx::Ref>;
+                }
+
+                impl #rust_tag {
+                    pub fn new() -> justcxx::CppOwned<#rust_tag> {
+                        unsafe {
+                            let ptr = ffi::#new_fn();
+                            CppObject { inner: ptr, _marker: std::marker::PhantomData}
+                        }
+                    }
+                }
+            });
+
+            if bind_context.is_cloneable(def) {
+                items.push(generate_clone_impl(&rust_tag, &get_clone_fn_name(def)));
+            }
+            items.push(generate_debug_impl(
+                &rust_tag,
+                quote! { f.debug_map().entries(self.iter()).finish() },
+            ));
+            items.push(generate_container_to_value_impl(
+                &rust_tag,
+                quote! {
+                    Map(self
+                        .iter()
+                        .map(|(k, v)| (justcxx::ToValue::to_value(&k), justcxx::ToValue::to_value(&v)))
+                        .collect())
+                },
+            ));
+            if bind_context.is_constructible(def) {
+                let key_arg = match **key {
+                    TypeKind::Object(_) => {
+                        let owned = from_value_arg(key, quote! { key }, &quote! { ? });
+                        quote! { (#owned).as_ref() }
+                    }
+                    _ => from_value_arg(key, quote! { key }, &quote! { ? }),
+                };
+                let val_arg = from_value_arg(value, quote! { val }, &quote! { ? });
+                items.push(quote! {
+                    impl justcxx::FromValue for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned> {
+                        fn from_value(value: justcxx::Value) -> Option<Self> {
+                            let justcxx::Value::Map(entries) = value else {
+                                return None;
+                            };
+                            let mut out = <#rust_tag>::new();
+                            for (key, val) in entries {
+                                out.insert(#key_arg, #val_arg);
+                            }
+                            Some(out)
+                        }
+                    }
+                });
+            }
+            if bind_context.has_derive("Serialize") && bind_context.is_serializable(def) {
+                items.push(generate_container_serialize_impl(
+                    &rust_tag,
+                    quote! { collect_map },
+                ));
+            }
+            if bind_context.has_derive("Deserialize") && bind_context.is_deserializable(def) {
+                let key_ty = key.to_rust_wrapper_ret_type(None);
+                let val_ty = value.to_rust_wrapper_ret_type(None);
+                let as_arg = |ty: &TypeKind, name: TokenStream| match ty {
+                    TypeKind::String => quote! { &#name },
+                    _ => name,
+                };
+                let key_arg = as_arg(key, quote! { key });
+                let val_arg = as_arg(value, quote! { val });
+                items.push(generate_container_deserialize_impl(
+                    &rust_tag,
+                    "a map",
+                    quote! {
+                        fn visit_map<A: justcxx::serde::de::MapAccess<'de>>(
+                            self,
+                            mut map: A,
+                        ) -> Result<Self::Value, A::Error> {
+                            let mut out = <#rust_tag>::new();
+                            while let Some((key, val)) = map.next_entry::<#key_ty, #val_ty>()? {
+                                out.insert(#key_arg, #val_arg);
+                            }
+                            Ok(out)
+                        }
+                    },
+                    quote! { deserialize_map },
+                ));
+            }
+
+            generate_map_functions(&flat_name, rust_tag, key, value, &mut items);
+        }
+    }
+    quote! { #(#items)* }
+}
+
+fn generate_map_functions(
+    type_prefix: &str,
+    rust_tag: TokenStream,
+    key_kind: &TypeKind,
+    val_kind: &TypeKind,
+    items: &mut Vec<TokenStream>,
+) {
+    let len_fn = format_ident!("{}_len", type_prefix);
+    let get_fn = format_ident!("{}_get", type_prefix);
+
+    let get_mut_fn = format_ident!("{}_get_mut", type_prefix);
+    let insert_fn = format_ident!("{}_insert", type_prefix);
+    let remove_fn = format_ident!("{}_remove", type_prefix);
+    let contains_fn = format_ident!("{}_contains_key", type_prefix);
+    let clear_fn = format_ident!("{}_clear", type_prefix);
+
+    let iter_struct_name = format_ident!("{}_Iter", type_prefix);
+    let iter_mut_struct_name = format_ident!("{}_IterMut", type_prefix);
+    let entry_struct_name = format_ident!("{}_Entry", type_prefix);
+
+    let key_tag = key_kind.to_rust_tag();
+    let (key_arg_ty, key_pass_code) = match key_kind {
+        TypeKind::String => (quote! { &str }, quote! { key }),
+        TypeKind::Object(_) => (
+            quote! { justcxx::CppRef<'_, #key_tag> },
+            quote! { &*key.as_ptr() },
+        ),
+        _ => (quote! { #key_tag }, quote! { key }),
+    };
+
+    // Object keys stay borrowed for the whole entry, everything else is owned.
+    let (iter_key_ty, entry_key_arg, entry_key_ty, entry_key_init, entry_key_use) = match key_kind
+    {
+        TypeKind::String => (
+            quote! { String },
+            quote! { &str },
+            quote! { String },
+            quote! { key.to_owned() },
+            quote! { &key },
+        ),
+        TypeKind::Object(_) => (
+            quote! { justcxx::CppRef<'a, #key_tag> },
+            quote! { justcxx::CppRef<'b, #key_tag> },
+            quote! { justcxx::CppRef<'b, #key_tag> },
+            quote! { key },
+            quote! { key },
+        ),
+        _ => (
+            quote! { #key_tag },
+            quote! { #key_tag },
+            quote! { #key_tag },
+            quote! { key },
+            quote! { key },
+        ),
+    };
+
+    let val_tag = val_kind.to_rust_tag();
+    let is_obj = val_kind.is_object_value();
+    let keys_item_ty = if let TypeKind::Object(_) = key_kind {
+        quote! { justcxx::CppRef<'_, #key_tag> }
+    } else {
+        iter_key_ty.clone()
+    };
+    let values_item_ty = match val_kind {
+        TypeKind::String => quote! { String },
+        _ if is_obj => quote! { justcxx::CppRef<'_, #val_tag> },
+        _ => quote! { #val_tag },
+    };
+    let val_insert_code = if let TypeKind::String = val_kind {
+        quote! { &val }
+    } else {
+        quote! { val }
+    };
+
+    let (common_ret_ty, common_mapper) = if is_obj {
+        (
+            quote! { justcxx::CppRef<'a, #val_tag> },
+            quote! {
+                let ptr = ret.get_unchecked_mut() as *mut _;
+                CppObject { inner: ptr, _marker: std::marker::PhantomData }
+            },
+        )
+    } else {
+        (
+            if let TypeKind::String = val_kind {
+                quote! { String }
+            } else {
+                quote! { #val_tag }
+            },
+            quote! { ret },
+        )
+    };
+
+    let common_methods = quote! {
+        pub fn len(&self) -> usize {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#len_fn(&*ptr)
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        pub fn get(&self, key: #key_arg_ty) -> Option<#common_ret_ty> {
+            unsafe {
+                let ptr = self.as_ptr();
+                let mut_ptr = ptr as *mut _;
+                let pin_self = std::pin::Pin::new_unchecked(&mut *mut_ptr);
+
+                match ffi::#get_fn(pin_self, #key_pass_code) {
+                    Ok(ret) => {
+                        let val = { #common_mapper };
+                        Some(val)
+                    },
+                    Err(_) => None,
+                }
+            }
+        }
+
+        pub fn iter(&self) -> #iter_struct_name<'_, justcxx::Const> {
+            #iter_struct_name::new(self.as_ptr())
+        }
+
+        pub fn contains_key(&self, key: #key_arg_ty) -> bool {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#contains_fn(&*ptr, #key_pass_code)
+            }
+        }
+
+        pub fn keys(&self) -> impl Iterator<Item = #keys_item_ty> + '_ {
+            self.iter().map(|(k, _)| k)
+        }
+
+        pub fn values(&self) -> impl Iterator<Item = #values_item_ty> + '_ {
+            self.iter().map(|(_, v)| v)
+        }
+    };
+
+    let (get_mut_method, iter_mut_method) = if is_obj {
+        (
+            quote! {
+                pub fn get_mut(&mut self, key: #key_arg_ty) -> Option<justcxx::CppMut<'_, #val_tag>> {
+                    unsafe {
+                        let ptr = self.as_ptr();
+                        let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                        match ffi::#get_fn(pin_self, #key_pass_code) {
+                            Ok(ret) => {
+                                let ptr = ret.get_unchecked_mut() as *mut _;
+                                Some(CppObject { inner: ptr, _marker: std::marker::PhantomData })
+                            },
+                            Err(_) => None,
+                        }
+                    }
+                }
+            },
+            quote! {
+                pub fn iter_mut(&mut self) -> #iter_struct_name<'_, justcxx::Mut> {
+                    #iter_struct_name::new(self.as_ptr())
+                }
+
+                pub fn values_mut(&mut self) -> impl Iterator<Item = justcxx::CppMut<'_, #val_tag>> {
+                    self.iter_mut().map(|(_, v)| v)
+                }
+            },
+        )
+    } else {
+        let val_mut_ty = map_val_mut_ty(val_kind, &quote! { '_ });
+        (
+            quote! {
+                pub fn get_mut(&mut self, key: #key_arg_ty) -> Option<#val_mut_ty> {
+                    unsafe {
+                        let ptr = self.as_ptr();
+                        let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+                        ffi::#get_mut_fn(pin_self, #key_pass_code).ok()
+                    }
+                }
+            },
+            quote! {
+                pub fn iter_mut(&mut self) -> #iter_mut_struct_name<'_> {
+                    #iter_mut_struct_name::new(self.as_ptr())
+                }
+
+                pub fn values_mut(&mut self) -> impl Iterator<Item = #val_mut_ty> {
+                    self.iter_mut().map(|(_, v)| v)
+                }
+            },
+        )
+    };
+
+    let (val_owned_ty, val_pass_code, val_ret_mapper) = if is_obj {
+        (
+            quote! { justcxx::CppOwned<#val_tag> },
+            quote! { val.inner },
+            quote! { CppObject { inner: ret, _marker: std::marker::PhantomData } },
+        )
+    } else if let TypeKind::String = val_kind {
+        (quote! { String }, quote! { val }, quote! { ret })
+    } else {
+        (quote! { #val_tag }, quote! { val }, quote! { ret })
+    };
+    let val_arg_ty = if let TypeKind::String = val_kind {
+        quote! { &str }
+    } else {
+        val_owned_ty.clone()
+    };
+
+    let insert_body = if is_obj {
+        quote! {
+            let ret = ffi::#insert_fn(pin_self, #key_pass_code, #val_pass_code);
+            if ret.is_null() { None } else { Some(#val_ret_mapper) }
+        }
+    } else {
+        quote! {
+            let mut old = <#val_owned_ty>::default();
+            ffi::#insert_fn(pin_self, #key_pass_code, #val_pass_code, &mut old).then_some(old)
+        }
+    };
+
+    let iter_mut_ty = if is_obj {
+        quote! { #iter_struct_name<'x, justcxx::Mut> }
+    } else {
+        quote! { #iter_mut_struct_name<'x> }
+    };
+    let into_iter_mut_impl = quote! {
+        impl<'x, 'a, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x mut CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            type Item = <#iter_mut_ty as Iterator>::Item;
+            type IntoIter = #iter_mut_ty;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
         }
     };
 
     let mut_methods = quote! {
-        pub fn get_mut(&mut self, index: usize) -> Option<justcxx::CppMut<'_, #elem_ident>>{
-            unsafe{
+        #get_mut_method
+        #iter_mut_method
+
+        pub fn insert(&mut self, key: #key_arg_ty, val: #val_arg_ty) -> Option<#val_owned_ty> {
+            unsafe {
                 let ptr = self.as_ptr();
                 let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                match ffi::#get_mut_fn(pin_self, index) {
-                    Ok(ret_ref) => {
-                        let ret_ptr = ret_ref.get_unchecked_mut() as *mut _;
-                        Some(CppObject {
-                            inner: ret_ptr,
-                            _marker: std::marker::PhantomData
-                        })
-                    },
-                    Err(_) => None,
-                }
+                #insert_body
             }
         }
 
-        pub fn push(&mut self, val: justcxx::CppOwned<#elem_ident>){
-            unsafe{
+        pub fn remove(&mut self, key: #key_arg_ty) -> Option<#val_owned_ty> {
+            unsafe {
                 let ptr = self.as_ptr();
                 let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                ffi::#push_fn(pin_self, val.inner);
+                match ffi::#remove_fn(pin_self, #key_pass_code) {
+                    Ok(ret) => Some(#val_ret_mapper),
+                    Err(_) => None,
+                }
             }
         }
 
-        pub fn iter_mut(&mut self) -> impl Iterator<Item = justcxx::CppMut<'_, #elem_ident>> + '_{
-            unsafe{
+        pub fn clear(&mut self) {
+            unsafe {
                 let ptr = self.as_ptr();
-                (0..self.len()).map(
-                    move |index|{
-                        let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                        match ffi::#get_mut_fn(pin_self, index) {
-                            Ok(ret_ref) => {
-                                let ret_ptr = ret_ref.get_unchecked_mut() as *mut _;
-                                CppObject {
-                                    inner: ret_ptr,
-                                    _marker: std::marker::PhantomData
-                                }
-                            },
-                            Err(_) => panic!("Index out of bounds in iter_mut!"),
-                        }
-                    }
-                )
-
+                ffi::#clear_fn(std::pin::Pin::new_unchecked(&mut *ptr));
             }
         }
 
+        pub fn entry<'b>(&'b mut self, key: #entry_key_arg) -> #entry_struct_name<'b, 'a, S> {
+            #entry_struct_name {
+                map: self,
+                key: #entry_key_init,
+            }
+        }
     };
 
     items.push(quote! {
-        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>>
-            CppObject<'a, #rust_tag, M, S>
-        {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
             #common_methods
         }
-        impl<'a, S: justcxx::Storage<#rust_tag>>
-            CppObject<'a, #rust_tag, justcxx::Mut, S>
-        {
+        impl<'a, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, justcxx::Mut, S> {
             #mut_methods
         }
+
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> justcxx::CppContainer
+            for CppObject<'a, #rust_tag, M, S>
+        {
+            fn len(&self) -> usize {
+                self.len()
+            }
+        }
+
+        impl<'x, 'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> IntoIterator
+            for &'x CppObject<'a, #rust_tag, M, S>
+        {
+            type Item = <#iter_struct_name<'x, justcxx::Const> as Iterator>::Item;
+            type IntoIter = #iter_struct_name<'x, justcxx::Const>;
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> IntoIterator for CppObject<'a, #rust_tag, M, justcxx::Ref> {
+            type Item = <#iter_struct_name<'a, M> as Iterator>::Item;
+            type IntoIter = #iter_struct_name<'a, M>;
+            fn into_iter(self) -> Self::IntoIter {
+                #iter_struct_name::new(self.as_ptr())
+            }
+        }
+
+        #into_iter_mut_impl
     });
-}
 
-pub fn generate_map_wrappers(map_defs: &HashSet<TypeKind>) -> TokenStream {
-    let mut items: Vec<TokenStream> = Vec::new();
+    let (entry_ret_ty, entry_get) = if is_obj {
+        (
+            quote! { justcxx::CppMut<'b, #val_tag> },
+            quote! { map.get_mut(#entry_key_use).unwrap() },
+        )
+    } else if val_kind.is_scalar() {
+        (
+            quote! { &'b mut #val_tag },
+            quote! { map.get_mut(#entry_key_use).unwrap() },
+        )
+    } else {
+        (quote! { String }, quote! { map.get(#entry_key_use).unwrap() })
+    };
 
-    let mut sorted_defs: Vec<&TypeKind> = map_defs.iter().collect();
-    sorted_defs.sort_by(|a, b| a.get_flat_name().cmp(&b.get_flat_name()));
+    items.push(quote! {
+        pub struct #entry_struct_name<'b, 'a, S: justcxx::Storage<#rust_tag>> {
+            map: &'b mut CppObject<'a, #rust_tag, justcxx::Mut, S>,
+            key: #entry_key_ty,
+        }
 
-    for def in sorted_defs {
-        if let TypeKind::Map {
-            key,
-            value,
-            is_val_ptr: _,
-        } = def
-        {
-            let rust_tag = def.to_rust_tag();
-            let ffi_type = def.to_ffi_type_name_only();
-            let flat_name = def.get_flat_name();
-            let new_fn = format_ident!("make_{}_new", &flat_name);
+        impl<'b, 'a, S: justcxx::Storage<#rust_tag>> #entry_struct_name<'b, 'a, S> {
+            pub fn key(&self) -> &#entry_key_ty {
+                &self.key
+            }
 
-            items.push(quote! {
-                impl justcxx::CppClass for #rust_tag {
-                    type FfiType = ffi::#ffi_type;
-                }
-                impl justcxx::CppTypeAliases for #rust_tag {
-                    type Owned = CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>;
-                    type Ref<'a> = CppObject<'a, #rust_tag, justcxx::Const, justcxx::Ref>;
-                    type Mut<'a> = CppObject<'a, #rust_tag, justcxx::Mut, justcxx::Ref>;
-                }
+            pub fn or_insert(self, val: #val_owned_ty) -> #entry_ret_ty {
+                self.or_insert_with(|| val)
+            }
 
-                impl #rust_tag {
-                    pub fn new() -> justcxx::CppOwned<#rust_tag> {
-                        unsafe {
-                            let ptr = ffi::#new_fn();
-                            CppObject { inner: ptr, _marker: std::marker::PhantomData}
-                        }
-                    }
+            pub fn or_insert_with(self, f: impl FnOnce() -> #val_owned_ty) -> #entry_ret_ty {
+                let map = self.map;
+                let key = self.key;
+                if !map.contains_key(#entry_key_use) {
+                    let val = f();
+                    map.insert(#entry_key_use, #val_insert_code);
                 }
-            });
+                #entry_get
+            }
+        }
+    });
 
-            generate_map_functions(&flat_name, rust_tag, &**key, &**value, &mut items);
+    if key_kind.is_bulk_copyable() && val_kind.is_bulk_copyable() {
+        generate_map_bulk(type_prefix, &rust_tag, key_kind, val_kind, items);
+    }
+
+    generate_map_iter_struct(type_prefix, key_kind, val_kind, &iter_struct_name, items);
+    if !is_obj {
+        generate_map_iter_mut_struct(type_prefix, key_kind, val_kind, &iter_mut_struct_name, items);
+    }
+}
+
+/// Primitive values are lent as `&mut T`, strings as the `std::string` itself.
+fn map_val_mut_ty(val_kind: &TypeKind, lifetime: &TokenStream) -> TokenStream {
+    match val_kind {
+        TypeKind::String => quote! { std::pin::Pin<&#lifetime mut cxx::CxxString> },
+        _ => {
+            let tag = val_kind.to_rust_tag();
+            quote! { &#lifetime mut #tag }
         }
     }
-    quote! { #(#items)* }
 }
 
-fn generate_map_functions(
+fn map_iter_key(key_kind: &TypeKind) -> (TokenStream, TokenStream) {
+    let key_tag = key_kind.to_rust_tag();
+    match key_kind {
+        TypeKind::String => (quote! { String }, quote! { k }),
+        TypeKind::Object(_) => (
+            quote! { justcxx::CppRef<'a, #key_tag> },
+            quote! {
+                let k_ptr = (k as *const _) as *mut _;
+                CppObject { inner: k_ptr, _marker: std::marker::PhantomData }
+            },
+        ),
+        _ => (quote! { #key_tag }, quote! { k }),
+    }
+}
+
+fn generate_map_bulk(
     type_prefix: &str,
-    rust_tag: TokenStream,
+    rust_tag: &TokenStream,
     key_kind: &TypeKind,
     val_kind: &TypeKind,
     items: &mut Vec<TokenStream>,
 ) {
-    let len_fn = format_ident!("{}_len", type_prefix);
-    let get_fn = format_ident!("{}_get", type_prefix);
-
-    let iter_new_fn = format_ident!("{}_iter_new", type_prefix);
-    let iter_struct_name = format_ident!("{}_Iter", type_prefix);
+    // Floats are not `Hash`, so there is no `HashMap` to convert from or to.
+    if matches!(key_kind, TypeKind::Primitive(p) if p == "f32" || p == "f64") {
+        return;
+    }
 
-    let (key_arg_ty, key_pass_code) = if let TypeKind::String = key_kind {
-        (quote! { &str }, quote! { key })
+    let to_vecs_fn = format_ident!("{}_to_vecs", type_prefix);
+    let from_vecs_fn = format_ident!("make_{}_from_vecs", type_prefix);
+    let (key_ty, key_use) = if let TypeKind::String = key_kind {
+        (quote! { String }, quote! { &k })
     } else {
-        let t = key_kind.to_rust_tag();
-        (quote! { #t }, quote! { key })
+        (key_kind.to_rust_tag(), quote! { k })
     };
-
-    let val_tag = val_kind.to_rust_tag();
-    let is_obj = val_kind.is_object_value();
-
-    let (common_ret_ty, common_mapper) = if is_obj {
-        (
-            quote! { justcxx::CppRef<'a, #val_tag> },
-            quote! {
-                let ptr = ret.get_unchecked_mut() as *mut _;
-                CppObject { inner: ptr, _marker: std::marker::PhantomData }
-            },
-        )
+    let val_ty = val_kind.to_rust_wrapper_ret_type(None);
+    let val_use = if let TypeKind::String = val_kind {
+        quote! { &v }
     } else {
-        (
-            if let TypeKind::String = val_kind {
-                quote! { String }
-            } else {
-                quote! { #val_tag }
-            },
-            quote! { ret },
-        )
+        quote! { v }
     };
 
-    // let mut_methods_impl = if is_obj {
-    //     let ret_ty = quote! { justcxx::CppMut<'a, #val_tag> };
-    //     let mapper = quote! {
-    //         // ret 是 Pin<&mut T>
-    //         let ptr = ret.get_unchecked_mut() as *mut _;
-    //         CppObject { inner: ptr, _marker: std::marker::PhantomData }
-    //     };
-
-    //     quote! {
-    //         pub fn get_mut(&mut self, key: #key_arg_ty) -> Option<#ret_ty> {
-    //             unsafe {
-    //                 let ptr = self.as_ptr();
-    //                 let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-    //                 match ffi::#get_fn(pin_self, #key_pass_code) {
-    //                     Ok(ret) => {
-    //                         let val = { #mapper };
-    //                         Some(val)
-    //                     },
-    //                     Err(_) => None,
-    //                 }
-    //             }
-    //         }
-    //     }
-    // } else {
-    //     quote! {}
-    // };
-
-    let common_methods = quote! {
-        pub fn len(&self) -> usize {
-            unsafe {
-                let ptr = self.as_ptr();
-                ffi::#len_fn(&*ptr)
+    items.push(quote! {
+        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
+            pub fn to_hash_map(&self) -> std::collections::HashMap<#key_ty, #val_ty> {
+                let mut keys = Vec::new();
+                let mut vals = Vec::new();
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#to_vecs_fn(&*ptr, &mut keys, &mut vals);
+                }
+                keys.into_iter().zip(vals).collect()
             }
         }
 
-        pub fn get(&self, key: #key_arg_ty) -> Option<#common_ret_ty> {
-            unsafe {
-                let ptr = self.as_ptr();
-                let mut_ptr = ptr as *mut _;
-                let pin_self = std::pin::Pin::new_unchecked(&mut *mut_ptr);
-
-                match ffi::#get_fn(pin_self, #key_pass_code) {
-                    Ok(ret) => {
-                        let val = { #common_mapper };
-                        Some(val)
-                    },
-                    Err(_) => None,
+        impl From<std::collections::HashMap<#key_ty, #val_ty>>
+            for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>
+        {
+            fn from(map: std::collections::HashMap<#key_ty, #val_ty>) -> Self {
+                let (keys, vals): (Vec<_>, Vec<_>) = map.into_iter().unzip();
+                unsafe {
+                    let ptr = ffi::#from_vecs_fn(&keys, &vals);
+                    CppObject { inner: ptr, _marker: std::marker::PhantomData }
                 }
             }
         }
 
-        pub fn iter(&self) -> #iter_struct_name<'a, justcxx::Const> {
-            unsafe {
-                let ptr = self.as_ptr();
-                let mut_ptr = ptr as *mut _;
-                let pin_self = std::pin::Pin::new_unchecked(&mut *mut_ptr);
-
-                let ctx = ffi::#iter_new_fn(pin_self);
-                #iter_struct_name::<'a, justcxx::Const> { ctx, _marker: std::marker::PhantomData }
+        impl<'a, S: justcxx::Storage<#rust_tag>> Extend<(#key_ty, #val_ty)>
+            for CppObject<'a, #rust_tag, justcxx::Mut, S>
+        {
+            fn extend<I: IntoIterator<Item = (#key_ty, #val_ty)>>(&mut self, iter: I) {
+                for (k, v) in iter {
+                    self.insert(#key_use, #val_use);
+                }
             }
         }
-    };
 
-    items.push(quote! {
-        impl<'a, M: justcxx::Mode, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, M, S> {
-            #common_methods
+        impl FromIterator<(#key_ty, #val_ty)>
+            for CppObject<'static, #rust_tag, justcxx::Mut, justcxx::Owned>
+        {
+            fn from_iter<I: IntoIterator<Item = (#key_ty, #val_ty)>>(iter: I) -> Self {
+                let map: std::collections::HashMap<#key_ty, #val_ty> = iter.into_iter().collect();
+                Self::from(map)
+            }
         }
-        // impl<'a, S: justcxx::Storage<#rust_tag>> CppObject<'a, #rust_tag, justcxx::Mut, S> {
-        //     #mut_methods_impl
-        // }
     });
-
-    generate_map_iter_struct(type_prefix, key_kind, val_kind, &iter_struct_name, items);
 }
 
 fn generate_map_iter_struct(
@@ -602,23 +2748,21 @@ fn generate_map_iter_struct(
     items: &mut Vec<TokenStream>,
 ) {
     let iter_ctx_name = format_ident!("{}_IterCtx", prefix);
+    let iter_new_fn = format_ident!("{}_iter_new", prefix);
+    let len_fn = format_ident!("{}_len", prefix);
+    let ffi_type = format_ident!("{}", prefix);
     let iter_key_fn = format_ident!("{}_iter_key", prefix);
     let iter_val_fn = format_ident!("{}_iter_val", prefix);
     let iter_step_fn = format_ident!("{}_iter_step", prefix);
     let iter_is_end_fn = format_ident!("{}_iter_is_end", prefix);
 
-    let key_tag = key_kind.to_rust_tag();
     let val_tag = val_kind.to_rust_tag();
 
-    let (iter_key_ty, key_mapper) = if let TypeKind::String = key_kind {
-        (quote! { String }, quote! { k })
-    } else {
-        (quote! { #key_tag }, quote! { k })
-    };
+    let (iter_key_ty, key_mapper) = map_iter_key(key_kind);
 
     let (iter_val_ty, val_mapper) = if val_kind.is_object_value() {
         (
-            quote! { justcxx::CppRef<'a, #val_tag> },
+            quote! { CppObject<'a, #val_tag, M, justcxx::Ref> },
             quote! {
                 let v_ptr = v_raw.get_unchecked_mut() as *mut _;
                 CppObject { inner: v_ptr, _marker: std::marker::PhantomData }
@@ -633,9 +2777,22 @@ fn generate_map_iter_struct(
     items.push(quote! {
         pub struct #struct_name<'a, M: justcxx::Mode> {
             ctx: cxx::UniquePtr<ffi::#iter_ctx_name>,
+            remaining: usize,
             _marker: std::marker::PhantomData<(&'a (), M)>,
         }
 
+        impl<'a, M: justcxx::Mode> #struct_name<'a, M> {
+            fn new(map: *mut ffi::#ffi_type) -> Self {
+                unsafe {
+                    let remaining = ffi::#len_fn(&*map);
+                    let ctx = ffi::#iter_new_fn(std::pin::Pin::new_unchecked(&mut *map));
+                    Self { ctx, remaining, _marker: std::marker::PhantomData }
+                }
+            }
+        }
+
+        impl<'a, M: justcxx::Mode> ExactSizeIterator for #struct_name<'a, M> {}
+
         impl<'a, M: justcxx::Mode> Iterator for #struct_name<'a, M> {
             type Item = (#iter_key_ty, #iter_val_ty);
 
@@ -645,15 +2802,87 @@ fn generate_map_iter_struct(
                         return None;
                     }
                     let k = ffi::#iter_key_fn(self.ctx.pin_mut());
+                    let final_key = { #key_mapper };
+
                     let v_raw = ffi::#iter_val_fn(self.ctx.pin_mut());
+                    let final_val = { #val_mapper };
+
+                    ffi::#iter_step_fn(self.ctx.pin_mut());
+                    self.remaining -= 1;
+                    Some((final_key, final_val))
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.remaining, Some(self.remaining))
+            }
+        }
+    });
+}
+
+/// `iter_mut` over maps of primitives and strings, lending each value in place.
+fn generate_map_iter_mut_struct(
+    prefix: &str,
+    key_kind: &TypeKind,
+    val_kind: &TypeKind,
+    struct_name: &syn::Ident,
+    items: &mut Vec<TokenStream>,
+) {
+    let iter_ctx_name = format_ident!("{}_IterCtx", prefix);
+    let iter_new_fn = format_ident!("{}_iter_new", prefix);
+    let len_fn = format_ident!("{}_len", prefix);
+    let ffi_type = format_ident!("{}", prefix);
+    let iter_key_fn = format_ident!("{}_iter_key", prefix);
+    let iter_val_mut_fn = format_ident!("{}_iter_val_mut", prefix);
+    let iter_step_fn = format_ident!("{}_iter_step", prefix);
+    let iter_is_end_fn = format_ident!("{}_iter_is_end", prefix);
+
+    let (iter_key_ty, key_mapper) = map_iter_key(key_kind);
+    let iter_val_ty = map_val_mut_ty(val_kind, &quote! { 'a });
+
+    items.push(quote! {
+        pub struct #struct_name<'a> {
+            ctx: cxx::UniquePtr<ffi::#iter_ctx_name>,
+            remaining: usize,
+            _marker: std::marker::PhantomData<&'a mut ()>,
+        }
+
+        impl<'a> #struct_name<'a> {
+            fn new(map: *mut ffi::#ffi_type) -> Self {
+                unsafe {
+                    let remaining = ffi::#len_fn(&*map);
+                    let ctx = ffi::#iter_new_fn(std::pin::Pin::new_unchecked(&mut *map));
+                    Self { ctx, remaining, _marker: std::marker::PhantomData }
+                }
+            }
+        }
+
+        impl<'a> ExactSizeIterator for #struct_name<'a> {}
+
+        impl<'a> Iterator for #struct_name<'a> {
+            type Item = (#iter_key_ty, #iter_val_ty);
 
+            fn next(&mut self) -> Option<Self::Item> {
+                unsafe {
+                    if ffi::#iter_is_end_fn(self.ctx.pin_mut()) {
+                        return None;
+                    }
+                    let k = ffi::#iter_key_fn(self.ctx.pin_mut());
                     let final_key = { #key_mapper };
-                    let final_val = { #val_mapper };
+
+                    // the value lives in the map, not in the iterator context
+                    let ctx = std::pin::Pin::new_unchecked(&mut *(self.ctx.as_mut_ptr()));
+                    let final_val = ffi::#iter_val_mut_fn(ctx);
 
                     ffi::#iter_step_fn(self.ctx.pin_mut());
+                    self.remaining -= 1;
                     Some((final_key, final_val))
                 }
             }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.remaining, Some(self.remaining))
+            }
         }
     });
 }
@@ -700,7 +2929,6 @@ fn gen_val_field(class_name: &Ident, field: &FieldDef) -> MethodGroups {
 
 fn gen_obj_field(class_name: &Ident, field: &FieldDef) -> MethodGroups {
     let ty = &field.ty;
-    let is_readonly = field.is_readonly;
     let field_name = &field.name;
     let get_name = field.get_ffi_get_name(class_name);
 
@@ -710,78 +2938,66 @@ fn gen_obj_field(class_name: &Ident, field: &FieldDef) -> MethodGroups {
     };
     let ret_ty_const = ref_const.to_rust_wrapper_ret_type(Some(&quote! { 'a }));
 
-    let ref_mut = TypeKind::Reference {
-        inner: Box::new(ty.clone()),
-        is_mut: true,
-    };
-    let ret_ty_mut = ref_mut.to_rust_wrapper_ret_type(Some(&quote! { 'a }));
-
-    match is_readonly {
-        true => {
-            let body = ref_const.gen_ret_conversion(quote! { ffi::#get_name(&*ptr) });
-            let common = vec![quote! {
-                pub fn #field_name(&self) -> #ret_ty_const {
-                    unsafe { let ptr = self.as_ptr(); #body }
-                }
-            }];
-
-            MethodGroups {
-                universal_common: vec![],
-                universal_mut: vec![],
-                ref_const: common,
-                ref_mut: vec![],
-                aux: None,
-            }
-        }
-
-        false => {
-            let body_mut = ref_mut.gen_ret_conversion(
-                quote! { ffi::#get_name(std::pin::Pin::new_unchecked(&mut *ptr)) },
-            );
-            let consts = vec![quote! {
-                pub fn #field_name(&self) -> #ret_ty_const {
-                    unsafe {
-                        let ptr = self.as_ptr();
-                        #body_mut
-                    }
-                }
-            }];
+    let ref_mut = TypeKind::Reference {
+        inner: Box::new(ty.clone()),
+        is_mut: true,
+    };
+    let ret_ty_mut = ref_mut.to_rust_wrapper_ret_type(Some(&quote! { 'a }));
 
-            let body_mut = ref_mut.gen_ret_conversion(
-                quote! { ffi::#get_name(std::pin::Pin::new_unchecked(&mut *ptr)) },
-            );
-            let mut muts = vec![quote! {
-                pub fn #field_name(&mut self) -> #ret_ty_mut {
-                    unsafe {
-                        let ptr = self.as_ptr();
-                        #body_mut
-                    }
+    let mut consts = Vec::new();
+    let mut muts = Vec::new();
+    if field.has_mut_getter() {
+        let body_mut = ref_mut.gen_ret_conversion(
+            quote! { ffi::#get_name(std::pin::Pin::new_unchecked(&mut *ptr)) },
+        );
+        consts.push(quote! {
+            pub fn #field_name(&self) -> #ret_ty_const {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    #body_mut
                 }
-            }];
+            }
+        });
+        muts.push(quote! {
+            pub fn #field_name(&mut self) -> #ret_ty_mut {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    #body_mut
+                }
+            }
+        });
+    } else {
+        let body = ref_const.gen_ret_conversion(quote! { ffi::#get_name(&*ptr) });
+        consts.push(quote! {
+            pub fn #field_name(&self) -> #ret_ty_const {
+                unsafe { let ptr = self.as_ptr(); #body }
+            }
+        });
+    }
 
-            let set_name = field.get_wrapper_set_name();
-            let ffi_set = field.get_ffi_set_name(class_name);
-            let arg_ty = ty.to_rust_wrapper_arg_type();
-            let arg_conv = ty.gen_arg_conversion(&format_ident!("val"));
+    if !field.is_readonly {
+        let set_name = field.get_wrapper_set_name();
+        let ffi_set = field.get_ffi_set_name(class_name);
+        let arg_ty = ty.to_rust_wrapper_arg_type();
+        let arg_conv = ty.gen_arg_conversion(&format_ident!("val"));
 
-            muts.push(quote! {
-                pub fn #set_name(&mut self, val: #arg_ty) {
-                    unsafe {
-                        let ptr = self.as_ptr();
-                        let pin = std::pin::Pin::new_unchecked(&mut *ptr);
-                        ffi::#ffi_set(pin, #arg_conv);
-                    }
+        muts.push(quote! {
+            pub fn #set_name(&mut self, val: #arg_ty) {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    let pin = std::pin::Pin::new_unchecked(&mut *ptr);
+                    ffi::#ffi_set(pin, #arg_conv);
                 }
-            });
-
-            MethodGroups {
-                universal_common: vec![],
-                universal_mut: vec![],
-                ref_const: consts,
-                ref_mut: muts,
-                aux: None,
             }
-        }
+        });
+    }
+
+    MethodGroups {
+        universal_common: vec![],
+        universal_mut: vec![],
+        ref_const: consts,
+        ref_mut: muts,
+        aux: None,
     }
 }
 
@@ -845,7 +3061,7 @@ fn gen_opt_field(class_name: &Ident, field: &FieldDef, inner: &TypeKind) -> Meth
 
             let set_name = field.get_wrapper_set_name();
             let ffi_set = field.get_ffi_set_name(class_name);
-            let arg_ty = TypeKind::new_unique_ptr(inner.clone()).to_rust_wrapper_arg_type();
+            let arg_ty = inner.to_rust_wrapper_arg_type();
             let arg_conv = inner.gen_arg_conversion(&format_ident!("val"));
 
             muts.push(quote! {
@@ -857,6 +3073,7 @@ fn gen_opt_field(class_name: &Ident, field: &FieldDef, inner: &TypeKind) -> Meth
                     }
                 }
             });
+            muts.push(gen_opt_clear(class_name, field));
 
             MethodGroups {
                 universal_common: vec![],
@@ -885,15 +3102,18 @@ fn gen_opt_field(class_name: &Ident, field: &FieldDef, inner: &TypeKind) -> Meth
                 let arg_ty = inner.to_rust_wrapper_arg_type();
                 let arg_conv = inner.gen_arg_conversion(&format_ident!("val"));
 
-                vec![quote! {
-                    pub fn #set_name(&mut self, val: #arg_ty) {
-                        unsafe {
-                            let ptr = self.as_ptr();
-                            let pin = std::pin::Pin::new_unchecked(&mut *ptr);
-                            ffi::#ffi_set(pin, #arg_conv);
+                vec![
+                    quote! {
+                        pub fn #set_name(&mut self, val: #arg_ty) {
+                            unsafe {
+                                let ptr = self.as_ptr();
+                                let pin = std::pin::Pin::new_unchecked(&mut *ptr);
+                                ffi::#ffi_set(pin, #arg_conv);
+                            }
                         }
-                    }
-                }]
+                    },
+                    gen_opt_clear(class_name, field),
+                ]
             } else {
                 vec![]
             };
@@ -909,11 +3129,71 @@ fn gen_opt_field(class_name: &Ident, field: &FieldDef, inner: &TypeKind) -> Meth
     }
 }
 
+fn gen_opt_clear(class_name: &Ident, field: &FieldDef) -> TokenStream {
+    let clear_name = field.get_wrapper_clear_name();
+    let ffi_clear = field.get_ffi_clear_name(class_name);
+    quote! {
+        pub fn #clear_name(&mut self) {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#ffi_clear(std::pin::Pin::new_unchecked(&mut *ptr));
+            }
+        }
+    }
+}
+
+/// Fields of `#[contiguous]` types borrow their storage as a slice.
+fn gen_slice_field(
+    class_name: &Ident,
+    field: &FieldDef,
+    slice: &TypeKind,
+    is_mut: bool,
+) -> MethodGroups {
+    let field_name = &field.name;
+    let get_name = field.get_ffi_get_name(class_name);
+    let ret_ty = TypeKind::new_const_ref(slice.clone()).to_rust_wrapper_ret_type(None);
+
+    let common = vec![quote! {
+        pub fn #field_name(&self) -> #ret_ty {
+            unsafe {
+                let ptr = self.as_ptr();
+                ffi::#get_name(&*ptr)
+            }
+        }
+    }];
+
+    let muts = if is_mut {
+        let mut_name = format_ident!("{}_mut", field_name);
+        let get_mut_name = format_ident!("{}_mut", get_name);
+        let ret_ty_mut = field.ty.to_rust_wrapper_ret_type(None);
+        vec![quote! {
+            pub fn #mut_name(&mut self) -> #ret_ty_mut {
+                unsafe {
+                    let ptr = self.as_ptr();
+                    ffi::#get_mut_name(std::pin::Pin::new_unchecked(&mut *ptr))
+                }
+            }
+        }]
+    } else {
+        vec![]
+    };
+
+    MethodGroups {
+        universal_common: common,
+        universal_mut: muts,
+        ref_const: vec![],
+        ref_mut: vec![],
+        aux: None,
+    }
+}
+
 fn generate_wrapper_field(class: &ClassModel, field: &FieldDef) -> MethodGroups {
     let class_name = &class.name;
 
     match &field.ty {
-        TypeKind::Primitive(_) | TypeKind::String => gen_val_field(class_name, field),
+        TypeKind::Primitive(_) | TypeKind::String | TypeKind::Enum(_) => {
+            gen_val_field(class_name, field)
+        }
 
         TypeKind::Object(_) | TypeKind::Map { .. } | TypeKind::Vector { .. } => {
             gen_obj_field(class_name, field)
@@ -921,6 +3201,10 @@ fn generate_wrapper_field(class: &ClassModel, field: &FieldDef) -> MethodGroups
 
         TypeKind::Option(inner) => gen_opt_field(class_name, field, inner),
 
+        TypeKind::Reference { inner, is_mut } if matches!(**inner, TypeKind::Slice(_)) => {
+            gen_slice_field(class_name, field, inner, *is_mut)
+        }
+
         _ => MethodGroups {
             universal_common: vec![],
             universal_mut: vec![],
@@ -931,250 +3215,409 @@ fn generate_wrapper_field(class: &ClassModel, field: &FieldDef) -> MethodGroups
     }
 }
 
-fn generate_wrapper_method(
-    class: &ClassModel,
-    method: &MethodDef,
-) -> (
-    Vec<TokenStream>,
-    Vec<TokenStream>,
-    Vec<TokenStream>,
-    Option<TokenStream>,
-) {
+fn generate_wrapper_method(class: &ClassModel, method: &MethodDef) -> MethodOutputs {
     let class_name = &class.name;
-    let mut common_methods = Vec::new();
-    let mut mut_methods = Vec::new();
-    let mut static_methods = Vec::new();
-    let mut aux_items = None;
+    let mut out = MethodOutputs::default();
 
     match method {
         MethodDef::Ctor(ctor) => {
-            let name = &ctor.rust_name;
             let ffi_unique_name = format_ident!("make_{}_{}", class_name, ctor.rust_name);
+            let ret_decl = match ctor.on_failure {
+                CtorFailure::Abort => quote! { -> justcxx::CppOwned<#class_name> },
+                CtorFailure::Result => {
+                    quote! { -> Result<justcxx::CppOwned<#class_name>, justcxx::CppException> }
+                }
+                CtorFailure::Option => quote! { -> Option<justcxx::CppOwned<#class_name>> },
+            };
 
-            let (args_def, args_call): (Vec<_>, Vec<_>) = ctor
+            let args_call: Vec<_> = ctor
                 .args
                 .iter()
-                .map(|arg| {
-                    let n = &arg.name;
-                    let ty = arg.ty.to_rust_wrapper_arg_type();
-                    let call = arg.ty.gen_arg_conversion(n);
-                    (quote! { #n: #ty }, call)
-                })
-                .unzip();
-
-            static_methods.push(quote! {
-                pub fn #name(#(#args_def),*) -> justcxx::CppOwned<#class_name> {
-                    unsafe {
-                        let unique_ptr = ffi::#ffi_unique_name(#(#args_call),*);
-                        CppObject {
-                            inner: unique_ptr,
-                            _marker: std::marker::PhantomData,
-                        }
+                .map(|arg| arg.ty.gen_arg_conversion(&arg.name))
+                .collect();
+            let (name, args_def, prelude) = prepare_default_args(
+                class_name,
+                &ctor.rust_name,
+                &ctor.args,
+                quote! {},
+                &ret_decl,
+                &mut out.statics,
+                &mut out.aux,
+            );
+
+            let wrap = quote! {
+                |unique_ptr| CppObject {
+                    inner: unique_ptr,
+                    _marker: std::marker::PhantomData,
+                }
+            };
+            let call = quote! { ffi::#ffi_unique_name(#(#args_call),*) };
+            let body = match ctor.on_failure {
+                CtorFailure::Abort => quote! {
+                    CppObject {
+                        inner: #call,
+                        _marker: std::marker::PhantomData,
                     }
+                },
+                CtorFailure::Result => quote! { #call.map(#wrap) },
+                CtorFailure::Option => quote! {
+                    #call.ok().filter(|unique_ptr| !unique_ptr.is_null()).map(#wrap)
+                },
+            };
+
+            out.statics.push(quote! {
+                pub fn #name(#(#args_def),*) #ret_decl {
+                    #prelude
+                    unsafe { #body }
                 }
             });
         }
 
         MethodDef::Iter(iter) => {
-            generate_iterator_method(class_name, iter, &mut common_methods, &mut aux_items);
+            generate_iterator_method(class_name, iter, &mut out);
         }
 
         MethodDef::Method(func) => {
-            generate_normal_method(
-                class_name,
-                func,
-                &mut common_methods,
-                &mut mut_methods,
-                &mut static_methods,
-            );
+            generate_normal_method(class_name, func, &mut out);
         }
     }
-    (common_methods, mut_methods, static_methods, aux_items)
+    out
 }
 
-fn generate_iterator_method(
-    class_name: &Ident,
-    iter: &IterDef,
-    common_methods: &mut Vec<TokenStream>,
-    aux_items: &mut Option<TokenStream>,
-) {
+fn generate_iterator_method(class_name: &Ident, iter: &IterDef, out: &mut MethodOutputs) {
     let method_name = &iter.rust_name;
-    let yield_ty_kind = &iter.yield_ty;
-
     let names = IterNames::new(class_name, method_name);
-
-    let aux_struct = generate_iter_aux_struct(&names, yield_ty_kind, iter.is_iter_mut);
-
-    if let Some(existing) = aux_items {
-        *aux_items = Some(quote! { #existing #aux_struct });
-    } else {
-        *aux_items = Some(aux_struct);
-    }
-
-    let item_ty_tokens = yield_ty_kind.to_rust_wrapper_ret_type(Some(&quote! {'a}));
-
-    let wrapper_method =
-        generate_iter_wrapper_method(method_name, &names, &item_ty_tokens, iter.is_iter_mut);
-
-    common_methods.push(wrapper_method);
-}
-
-fn generate_iter_aux_struct(names: &IterNames, yield_ty: &TypeKind, is_owned: bool) -> TokenStream {
     let struct_name = &names.struct_name;
     let ctx_name = &names.ctx_name;
+    let new_fn = &names.new_fn;
     let next_fn = &names.next_fn;
+    let done_fn = &names.done_fn;
 
-    let item_ty = yield_ty.to_rust_wrapper_ret_type(Some(&quote! {'a}));
+    let item_ty = iter.yield_ty.to_rust_wrapper_ret_type(Some(&quote! { 'a }));
+    let conversion = iter.yield_ty.gen_ret_conversion(quote! { ffi::#next_fn(self.ctx.pin_mut()) });
 
-    let conversion = yield_ty.gen_ret_conversion(quote! {ret_raw});
-
-    let body = if is_owned {
+    let double_ended = if iter.double_ended {
+        let next_back_fn = &names.next_back_fn;
+        let conversion = iter
+            .yield_ty
+            .gen_ret_conversion(quote! { ffi::#next_back_fn(self.ctx.pin_mut()) });
         quote! {
-            let ret_raw = ffi::#next_fn(self.ctx.pin_mut());
-            if ret_raw.is_null() {
-                None
-            } else {
-                Some({ #conversion })
+            impl<'a> DoubleEndedIterator for #struct_name<'a> {
+                fn next_back(&mut self) -> Option<Self::Item> {
+                    unsafe {
+                        if ffi::#done_fn(&self.ctx) {
+                            return None;
+                        }
+                        Some({ #conversion })
+                    }
+                }
             }
         }
     } else {
-        quote! {
-             let ret_raw = ffi::#next_fn(self.ctx.pin_mut());
-             #conversion
-        }
+        quote! {}
     };
 
-    quote! {
-        #[allow(non_camel_case_types)]
-        pub struct #struct_name<'a, M: justcxx::Mode> {
-            ctx: cxx::UniquePtr<ffi::#ctx_name>,
-            _marker: std::marker::PhantomData<(&'a (), M)>,
-        }
+    push_aux(
+        &mut out.aux,
+        quote! {
+            #[allow(non_camel_case_types)]
+            pub struct #struct_name<'a> {
+                ctx: cxx::UniquePtr<ffi::#ctx_name>,
+                _marker: std::marker::PhantomData<&'a ()>,
+            }
 
-        impl<'a, M: justcxx::Mode> Iterator for #struct_name<'a, M> {
-            type Item = #item_ty;
+            impl<'a> Iterator for #struct_name<'a> {
+                type Item = #item_ty;
 
-            fn next(&mut self) -> Option<Self::Item> {
+                fn next(&mut self) -> Option<Self::Item> {
+                    unsafe {
+                        if ffi::#done_fn(&self.ctx) {
+                            return None;
+                        }
+                        Some({ #conversion })
+                    }
+                }
+            }
+
+            #double_ended
+        },
+    );
+
+    let method = |receiver: TokenStream, self_arg: TokenStream| {
+        quote! {
+            pub fn #method_name(#receiver) -> #struct_name<'_> {
                 unsafe {
-                    #body
+                    let ptr = self.as_ptr();
+                    #struct_name {
+                        ctx: ffi::#new_fn(#self_arg),
+                        _marker: std::marker::PhantomData,
+                    }
                 }
             }
         }
+    };
+
+    match iter.mode {
+        IterMode::Ref | IterMode::Copy => {
+            out.common.push(method(quote! { &self }, quote! { &*ptr }))
+        }
+        IterMode::Mut | IterMode::Drain => out.muts.push(method(
+            quote! { &mut self },
+            quote! { std::pin::Pin::new_unchecked(&mut *ptr) },
+        )),
     }
 }
 
-fn generate_iter_wrapper_method(
-    method_name: &Ident,
-    names: &IterNames,
-    yield_ty_tokens: &TokenStream,
-    is_iter_mut: bool,
-) -> TokenStream {
-    let struct_name = &names.struct_name;
-    let new_fn = &names.new_fn;
+struct DefaultArgs {
+    struct_name: Ident,
+    struct_def: TokenStream,
+    required_decl: Vec<TokenStream>,
+    required_names: Vec<Ident>,
+    prelude: TokenStream,
+}
+
+fn split_default_args(class_name: &Ident, rust_name: &Ident, args: &[Arg]) -> Option<DefaultArgs> {
+    let (required, defaulted): (Vec<&Arg>, Vec<&Arg>) =
+        args.iter().partition(|arg| arg.default.is_none());
+    if defaulted.is_empty() {
+        return None;
+    }
+
+    let struct_name = get_default_args_struct_name(class_name, rust_name);
+    let mut fields = Vec::new();
+    let mut inits = Vec::new();
+    let mut prelude = Vec::new();
+
+    for arg in defaulted {
+        let n = &arg.name;
+        let expr = arg.default.as_ref().unwrap();
+        if let TypeKind::String = arg.ty {
+            fields.push(quote! { pub #n: String });
+            inits.push(quote! { #n: String::from(#expr) });
+            prelude.push(quote! { let #n: &str = &args.#n; });
+        } else {
+            let ty = arg.ty.to_rust_wrapper_arg_type();
+            fields.push(quote! { pub #n: #ty });
+            inits.push(quote! { #n: #expr });
+            prelude.push(quote! { let #n = args.#n; });
+        }
+    }
+
+    let struct_def = quote! {
+        pub struct #struct_name {
+            #(#fields),*
+        }
+
+        impl Default for #struct_name {
+            fn default() -> Self {
+                Self { #(#inits),* }
+            }
+        }
+    };
+
+    Some(DefaultArgs {
+        struct_name,
+        struct_def,
+        required_decl: required
+            .iter()
+            .map(|arg| {
+                let n = &arg.name;
+                let ty = arg.ty.to_rust_wrapper_arg_type();
+                quote! { #n: #ty }
+            })
+            .collect(),
+        required_names: required.iter().map(|arg| arg.name.clone()).collect(),
+        prelude: quote! { #(#prelude)* },
+    })
+}
+
+/// Splits a signature with defaulted arguments into `name(required..)`, which
+/// fills in the defaults, and `name_with(required.., args)` that does the call.
+/// Returns the name, declaration and prelude the full wrapper should use.
+fn prepare_default_args(
+    class_name: &Ident,
+    rust_name: &Ident,
+    args: &[Arg],
+    receiver: TokenStream,
+    ret_decl: &TokenStream,
+    short_methods: &mut Vec<TokenStream>,
+    aux_items: &mut Option<TokenStream>,
+) -> (Ident, Vec<TokenStream>, TokenStream) {
+    let Some(defaults) = split_default_args(class_name, rust_name, args) else {
+        let args_decl = args
+            .iter()
+            .map(|arg| {
+                let n = &arg.name;
+                let ty = arg.ty.to_rust_wrapper_arg_type();
+                quote! { #n: #ty }
+            })
+            .collect();
+        return (rust_name.clone(), args_decl, quote! {});
+    };
 
-    let receiver = if is_iter_mut {
-        quote! { &mut self }
+    let DefaultArgs {
+        struct_name,
+        struct_def,
+        required_decl,
+        required_names,
+        prelude,
+    } = defaults;
+    push_aux(aux_items, struct_def);
+
+    let with_name = format_ident!("{}_with", rust_name);
+    let callee = if receiver.is_empty() {
+        quote! { Self::#with_name }
     } else {
-        quote! { &self }
+        quote! { self.#with_name }
     };
-    quote! {
-        pub fn #method_name(#receiver) -> impl Iterator<Item = #yield_ty_tokens> + 'a where M: 'a {
-            unsafe {
-                let ptr = self.as_ptr();
-                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                let ctx = ffi::#new_fn(pin_self);
-                #struct_name::<'a, M> {
-                    ctx,
-                    _marker: std::marker::PhantomData,
-                }
-            }
+    short_methods.push(quote! {
+        pub fn #rust_name(#receiver #(#required_decl),*) #ret_decl {
+            #callee(#(#required_names,)* Default::default())
         }
+    });
+
+    let mut args_decl = required_decl;
+    args_decl.push(quote! { args: #struct_name });
+    (with_name, args_decl, prelude)
+}
+
+fn push_aux(aux_items: &mut Option<TokenStream>, item: TokenStream) {
+    if let Some(existing) = aux_items {
+        *aux_items = Some(quote! { #existing #item });
+    } else {
+        *aux_items = Some(item);
     }
 }
 
-fn generate_normal_method(
-    class_name: &Ident,
-    func: &FnDef,
-    common_methods: &mut Vec<TokenStream>,
-    mut_methods: &mut Vec<TokenStream>,
-    static_methods: &mut Vec<TokenStream>,
-) {
-    let method_name = &func.rust_name;
+fn generate_normal_method(class_name: &Ident, func: &FnDef, out: &mut MethodOutputs) {
     let ffi_name = format_ident!("{}_method_{}", class_name, func.rust_name);
 
-    let (args_decl, args_call): (Vec<_>, Vec<_>) = func
+    // In/out strings go through a temporary CxxString, out arguments become
+    // locals that are returned after the call.
+    let mut pre_call = Vec::new();
+    let mut post_call = Vec::new();
+    let mut out_values = Vec::new();
+    let mut out_tys = Vec::new();
+
+    let args_call: Vec<_> = func
         .args
         .iter()
         .map(|arg| {
-            let n = &arg.name;
-            let ty = arg.ty.to_rust_wrapper_arg_type();
-            let call = arg.ty.gen_arg_conversion(n);
-            (quote! { #n: #ty }, call)
+            let name = &arg.name;
+            let TypeKind::Reference { inner, is_mut: true } = &arg.ty else {
+                return arg.ty.gen_arg_conversion(name);
+            };
+            let is_string = matches!(**inner, TypeKind::String);
+            let cxx_name = format_ident!("{}_cxx", name);
+
+            match (arg.is_out, is_string) {
+                (true, true) => {
+                    pre_call.push(quote! { cxx::let_cxx_string!(#cxx_name = ""); });
+                    out_values.push(quote! { #cxx_name.to_string_lossy().into_owned() });
+                    out_tys.push(quote! { String });
+                    quote! { #cxx_name.as_mut() }
+                }
+                (true, false) => {
+                    let ty = inner.to_rust_wrapper_arg_type();
+                    pre_call.push(quote! { let mut #name = <#ty>::default(); });
+                    out_values.push(quote! { #name });
+                    out_tys.push(ty);
+                    quote! { &mut #name }
+                }
+                (false, true) => {
+                    pre_call.push(quote! { cxx::let_cxx_string!(#cxx_name = #name.as_bytes()); });
+                    post_call.push(quote! { *#name = #cxx_name.to_string_lossy().into_owned(); });
+                    quote! { #cxx_name.as_mut() }
+                }
+                (false, false) => arg.ty.gen_arg_conversion(name),
+            }
         })
-        .unzip();
+        .collect();
 
-    let prepare_ptr = quote! {
-        let ptr = self.as_ptr();
+    let mut ret_tys: Vec<_> = func
+        .ret_ty
+        .iter()
+        .map(|ret| ret.to_rust_wrapper_ret_type(Some(&quote! {'a})))
+        .collect();
+    ret_tys.extend(out_tys);
+    let ret_decl = match ret_tys.as_slice() {
+        [] => quote! {},
+        [ty] => quote! { -> #ty },
+        tys => quote! { -> (#(#tys),*) },
     };
 
-    let ret_decl = if let Some(ret) = &func.ret_ty {
-        let ty = ret.to_rust_wrapper_ret_type(Some(&quote! {'a}));
-        quote! { -> #ty }
-    } else {
-        quote! {}
+    let (receiver, self_setup, self_arg) = match func.kind {
+        MethodKind::Static => (quote! {}, quote! {}, quote! {}),
+        MethodKind::Const => (
+            quote! { &self, },
+            quote! { let ptr = self.as_ptr(); },
+            quote! { &*ptr, },
+        ),
+        MethodKind::Mutable => (
+            quote! { &mut self, },
+            quote! {
+                let ptr = self.as_ptr();
+                let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
+            },
+            quote! { pin_self, },
+        ),
+        MethodKind::Consuming => (quote! { self, }, quote! {}, quote! { self.inner, }),
     };
 
-    match func.kind {
-        MethodKind::Static => {
-            let ffi_call_expr = quote! { ffi::#ffi_name(#(#args_call),*) };
-            let body = if let Some(ret) = &func.ret_ty {
-                ret.gen_ret_conversion(ffi_call_expr)
-            } else {
-                quote! { #ffi_call_expr; }
-            };
-
-            static_methods.push(quote! {
-                pub fn #method_name(#(#args_decl),*) #ret_decl {
-                    unsafe { #body }
-                }
-            });
+    let ffi_call_expr = quote! { ffi::#ffi_name(#self_arg #(#args_call),*) };
+    let body = if post_call.is_empty() && out_values.is_empty() {
+        if let Some(ret) = &func.ret_ty {
+            ret.gen_ret_conversion(ffi_call_expr)
+        } else {
+            quote! { #ffi_call_expr; }
         }
-
-        MethodKind::Const => {
-            let ffi_call_expr = quote! { ffi::#ffi_name(&*ptr, #(#args_call),*) };
-            let body = if let Some(ret) = &func.ret_ty {
-                ret.gen_ret_conversion(ffi_call_expr)
-            } else {
-                quote! { #ffi_call_expr; }
-            };
-
-            common_methods.push(quote! {
-                pub fn #method_name(&self, #(#args_decl),*) #ret_decl {
-                    unsafe {
-                        #prepare_ptr
-                        #body
-                    }
-                }
-            });
+    } else {
+        let mut results = Vec::new();
+        let call = if let Some(ret) = &func.ret_ty {
+            let conv = ret.gen_ret_conversion(ffi_call_expr);
+            results.push(quote! { ret });
+            quote! { let ret = { #conv }; }
+        } else {
+            quote! { #ffi_call_expr; }
+        };
+        results.extend(out_values);
+        let result = match results.as_slice() {
+            [] => quote! {},
+            [value] => quote! { #value },
+            values => quote! { (#(#values),*) },
+        };
+        quote! {
+            #call
+            #(#post_call)*
+            #result
         }
+    };
 
-        MethodKind::Mutable => {
-            let ffi_call_expr = quote! { ffi::#ffi_name(pin_self, #(#args_call),*) };
-            let body = if let Some(ret) = &func.ret_ty {
-                ret.gen_ret_conversion(ffi_call_expr)
-            } else {
-                quote! { #ffi_call_expr; }
-            };
-
-            mut_methods.push(quote! {
-                pub fn #method_name(&mut self, #(#args_decl),*) #ret_decl {
-                    unsafe {
-                        #prepare_ptr
-                        let pin_self = std::pin::Pin::new_unchecked(&mut *ptr);
-                        #body
-                    }
-                }
-            });
+    let methods = match func.kind {
+        MethodKind::Static => &mut out.statics,
+        MethodKind::Const => &mut out.common,
+        MethodKind::Mutable => &mut out.muts,
+        MethodKind::Consuming => &mut out.owned,
+    };
+    let visible_args: Vec<Arg> = func.args.iter().filter(|a| !a.is_out).cloned().collect();
+    let (method_name, args_decl, prelude) = prepare_default_args(
+        class_name,
+        &func.rust_name,
+        &visible_args,
+        receiver.clone(),
+        &ret_decl,
+        methods,
+        &mut out.aux,
+    );
+
+    methods.push(quote! {
+        pub fn #method_name(#receiver #(#args_decl),*) #ret_decl {
+            #prelude
+            #(#pre_call)*
+            unsafe {
+                #self_setup
+                #body
+            }
         }
-    }
+    });
 }
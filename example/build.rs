@@ -1,5 +1,3 @@
-use justcxx_build;
-
 fn main() {
     justcxx_build::bridge("src/lib.rs")
         .file("src/cpp/test.hh")
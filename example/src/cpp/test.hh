@@ -1,6 +1,9 @@
 #pragma once
+#include <istream>
 #include <memory>
 #include <optional>
+#include <ostream>
+#include <set>
 #include <string>
 #include <unordered_map>
 #include <vector>
@@ -14,8 +17,44 @@ struct Config {
         value = 56.0;
         name = "test";
     }
+    Config with_id(int new_id) const {
+        Config c = *this;
+        c.id = new_id;
+        return c;
+    }
+    Config& rename(const std::string& new_name) {
+        name = new_name;
+        return *this;
+    }
+    bool same_id(const Config& other) const {
+        return id == other.id;
+    }
 };
 
+inline std::ostream& operator<<(std::ostream& os, const Config& c) {
+    return os << c.id << " " << c.value << " " << c.name;
+}
+
+inline std::istream& operator>>(std::istream& is, Config& c) {
+    return is >> c.id >> c.value >> c.name;
+}
+struct Builder {
+    int id = 0;
+    std::string name = "built";
+    Builder& with_id(int v) {
+        id = v;
+        return *this;
+    }
+    Config build() && {
+        Config c;
+        c.id = id;
+        c.name = std::move(name);
+        return c;
+    }
+    int finish(int extra) && {
+        return id + extra;
+    }
+};
 
 struct Manager{
     Config config;
@@ -84,6 +123,7 @@ struct ConfigContainer {
     std::vector<Config> data;
     std::vector<std::string> names;
     std::vector<int> ids;
+    std::vector<bool> flags = {true, false, true};
     ConfigContainer() {
         data.emplace_back();
         data.back().id = 100;
@@ -132,6 +172,7 @@ struct MapExample{
     std::unordered_map<int, std::string> int_str_map;
     std::unordered_map<int, Config> int_config_map;
     std::unordered_map<std::string, Config> str_config_map;
+    std::unordered_map<std::string, int> str_int_map;
     MapExample(){
         int_config_map[10] = Config();
         int_config_map[10].id = 10;
@@ -148,4 +189,290 @@ struct MapExample{
 
 struct Ctor{
     int id;
-};
\ No newline at end of file
+};
+
+class Secret {
+public:
+    int id = 1;
+protected:
+    int code = 7;
+};
+
+class SecretVerbose {
+public:
+    int id = 2;
+protected:
+    int code = 9;
+};
+
+struct Canvas {
+    int w = 0;
+    int h = 0;
+    bool keep = false;
+    std::string label;
+    Canvas(int w_, int h_ = 10) : w(w_), h(h_) {}
+    void resize(int nw, int nh, bool k = true, std::string l = "resized") {
+        w = nw;
+        h = nh;
+        keep = k;
+        label = std::move(l);
+    }
+};
+
+struct Parser {
+    int base = 0;
+    bool try_parse(const std::string& s, int& out, std::string& err) const {
+        try {
+            out = std::stoi(s) + base;
+            return true;
+        } catch (const std::exception&) {
+            err = "not a number: " + s;
+            return false;
+        }
+    }
+    void split(int value, int& tens, int& ones) const {
+        tens = value / 10;
+        ones = value % 10;
+    }
+    void bump(int& counter, std::string& log) {
+        counter += 1;
+        log += "+";
+    }
+};
+
+struct Coord {
+    int x = 0;
+    int y = 0;
+    bool operator==(const Coord& other) const {
+        return x == other.x && y == other.y;
+    }
+};
+
+template <>
+struct std::hash<Coord> {
+    size_t operator()(const Coord& c) const noexcept {
+        return std::hash<int>()(c.x) ^ (std::hash<int>()(c.y) << 1);
+    }
+};
+
+struct Tile {
+    int kind = 0;
+};
+
+struct Board {
+    std::unordered_map<Coord, Tile> tiles;
+    Board() {
+        tiles[Coord{1, 2}] = Tile{5};
+    }
+};
+
+enum class Terrain { Grass, Water, Rock = 10 };
+
+struct Atlas {
+    Terrain base = Terrain::Grass;
+    std::unordered_map<Terrain, int> costs;
+    std::unordered_map<Terrain, Tile> samples;
+    std::vector<Terrain> route{Terrain::Grass, Terrain::Rock};
+    std::unordered_map<int, Terrain> layers{{0, Terrain::Water}};
+    Atlas() {
+        costs[Terrain::Grass] = 1;
+        costs[Terrain::Water] = 3;
+    }
+    Terrain next(Terrain t) const {
+        return t == Terrain::Grass ? Terrain::Water : Terrain::Rock;
+    }
+};
+
+template <typename T>
+class Buffer {
+public:
+    T* data() { return items.data(); }
+    const T* data() const { return items.data(); }
+    size_t size() const { return items.size(); }
+    void resize(size_t n) { items.resize(n); }
+
+private:
+    std::vector<T> items;
+};
+
+using SampleBuffer = Buffer<double>;
+
+struct Signal {
+    SampleBuffer samples;
+    Signal() {
+        samples.resize(3);
+        samples.data()[0] = 1.5;
+    }
+    const SampleBuffer& peek() const { return samples; }
+    SampleBuffer& window() { return samples; }
+};
+
+struct Inventory {
+    int total(const std::vector<Config>& items) const {
+        int sum = 0;
+        for (const auto& c : items) sum += c.id;
+        return sum;
+    }
+    int total_kinds(const std::vector<Tile>& tiles) const {
+        int sum = 0;
+        for (const auto& t : tiles) sum += t.kind;
+        return sum;
+    }
+    std::string join(const std::vector<std::string>& parts, const std::string& sep) const {
+        std::string out;
+        for (size_t i = 0; i < parts.size(); ++i) {
+            if (i > 0) out += sep;
+            out += parts[i];
+        }
+        return out;
+    }
+    int count_names(const std::vector<std::string>& names) const {
+        return static_cast<int>(names.size());
+    }
+};
+
+struct Node {
+    int value = 0;
+};
+
+struct Tree {
+    std::vector<Node> nodes;
+    Tree() : nodes{Node{1}, Node{2}, Node{3}} {}
+    std::vector<Node>& children() { return nodes; }
+    const std::vector<Node>& children() const { return nodes; }
+    std::vector<Node>::const_iterator nodes_begin() const { return nodes.begin() + 1; }
+    std::vector<Node>::const_iterator nodes_end() const { return nodes.end(); }
+    std::vector<Node> snapshot() const { return nodes; }
+};
+
+struct Tags {
+    std::vector<int> scores{3, 1, 2};
+    std::set<std::string> names{"b", "a"};
+    std::vector<int> doubled() const {
+        std::vector<int> out;
+        for (int s : scores)
+            out.push_back(s * 2);
+        return out;
+    }
+};
+
+class Registry {
+public:
+    int id = 0;
+    Registry() = default;
+    static std::unique_ptr<Registry> restore(int id) {
+        auto r = std::unique_ptr<Registry>(new Registry(id, 1));
+        r->nodes.push_back(Node{100});
+        return r;
+    }
+
+protected:
+    Registry(int id_, int count) : id(id_) {
+        for (int i = 0; i < count; ++i) nodes.push_back(Node{id_ * 10 + i});
+    }
+    std::vector<Node> nodes;
+    static int instances() { return 3; }
+    static std::unique_ptr<Registry> seeded(int id) {
+        return std::unique_ptr<Registry>(new Registry(id, 2));
+    }
+    std::vector<Node>::const_iterator begin() const { return nodes.begin(); }
+    std::vector<Node>::const_iterator end() const { return nodes.end(); }
+    const std::vector<Node>& all_nodes() const { return nodes; }
+    int operator()(int x) const { return id + x; }
+};
+
+inline std::unique_ptr<Registry> open_registry(int id) {
+    auto r = std::make_unique<Registry>();
+    r->id = id;
+    return r;
+}
+
+class Account {
+public:
+    int getId() const { return id_; }
+    void setId(int id) { id_ = id; }
+    const std::string& getOwner() const { return owner_; }
+    void setOwner(const std::string& owner) { owner_ = owner; }
+    const Config& getConfig() const { return config_; }
+    void setConfig(Config config) { config_ = std::move(config); }
+    const std::vector<int>& getHistory() const { return history_; }
+    int type = 3;
+
+private:
+    int id_ = 1;
+    std::string owner_ = "root";
+    Config config_;
+    std::vector<int> history_ = {1, 2};
+};
+
+class Shape {
+public:
+    virtual ~Shape() = default;
+    virtual double area() const = 0;
+};
+
+class Square : public Shape {
+public:
+    explicit Square(double side) : side_(side) {}
+    double area() const override { return side_ * side_; }
+
+private:
+    double side_;
+};
+
+inline std::unique_ptr<Shape> make_square(double side) {
+    return std::make_unique<Square>(side);
+}
+
+struct Ticket {
+    int number;
+    explicit Ticket(int n) : number(n) {}
+};
+
+struct Dispenser {
+    int next = 1;
+    Ticket issue() { return Ticket(next++); }
+    double measure(const Shape& shape) const { return shape.area(); }
+    bool is_next(const Ticket& t) const { return t.number + 1 == next; }
+};
+
+struct Connection {
+    std::string host;
+    explicit Connection(const std::string& h) : host(h) {
+        if (h.empty()) throw std::invalid_argument("empty host");
+    }
+};
+
+inline std::unique_ptr<Connection> find_connection(int id) {
+    if (id < 0) throw std::out_of_range("negative id");
+    return id > 0 ? std::make_unique<Connection>("host" + std::to_string(id)) : nullptr;
+}
+
+struct Endpoint {
+    int port;
+    std::string host;
+    std::optional<int> timeout;
+    std::optional<std::string> label;
+    Config config;
+    std::vector<int> retries;
+    bool secure = true;
+};
+
+struct Profile {
+    int level = 1;
+    std::string nickname = "anon";
+    int version = 5;
+    Config settings;
+    std::vector<std::string> tags;
+};
+
+struct Reservation {
+    std::optional<int> seats = 2;
+    std::optional<std::string> note;
+    std::optional<Config> config;
+};
+
+struct PtrHolder {
+    int id = 7;
+    std::unique_ptr<Config> owned;
+};
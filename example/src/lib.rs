@@ -2,12 +2,16 @@ use justcxx::bind;
 
 bind! {
     include!("test.hh");
+    #[derive(Clone, Debug, Mirror, Serialize, Deserialize)]
+    #[display]
+    #[from_str]
     struct Config{
         id: i32,
         value: f32,
         name: String,
     }
 
+    #[derive(Debug, Serialize, Deserialize)]
     struct Manager{
         config: Config,
     }
@@ -25,25 +29,31 @@ bind! {
         config: Config,
     }
 
+    #[derive(Serialize, Deserialize)]
     struct Chance{
         probability: Option<i32>,
     }
 
+    #[derive(Debug, Serialize)]
     struct Wallet{
         #[readonly]
         config: Option<Config>,
     }
 
+    #[derive(Debug, Serialize, Deserialize)]
     struct MapExample{
         int_str_map: Map<i32, String>,
         int_config_map: Map<i32, Config>,
-        str_config_map: Map<String, Config>
+        str_config_map: Map<String, Config>,
+        str_int_map: Map<String, i32>,
     }
 
+    #[derive(Debug, Serialize, Deserialize)]
     struct ConfigContainer{
         data: Vec<Config>,
         ids: Vec<i32>,
         names: Vec<String>,
+        flags: Vec<bool>,
     }
 
     impl Methods{
@@ -66,8 +76,94 @@ bind! {
         fn return_nums(&mut self) -> &mut Vec<i32>;
     }
 
+    impl Config{
+        fn with_id(&self, id: i32) -> Self;
+
+        fn rename(&mut self, name: String) -> &mut Self;
+
+        fn same_id(&self, other: &Self) -> bool;
+    }
+
+    struct Builder{
+        id: i32,
+    }
+
+    impl Builder{
+        fn with_id(&mut self, id: i32) -> &mut Self;
+
+        fn build(self) -> Config;
+
+        fn finish(mut self, extra: i32) -> i32;
+    }
+
+    struct Canvas{
+        w: i32,
+        h: i32,
+        keep: bool,
+        label: String,
+    }
+
+    impl Canvas{
+        fn new(w: i32, h: i32 = 10) -> Self;
+
+        fn resize(&mut self, w: i32, h: i32, keep: bool = true, label: String = "resized");
+    }
+
+    struct Parser{
+        base: i32,
+    }
+
+    impl Parser{
+        #[out(out, err)]
+        fn try_parse(&self, s: &String, out: &mut i32, err: &mut String) -> bool;
+
+        #[out(tens, ones)]
+        fn split(&self, value: i32, tens: &mut i32, ones: &mut i32);
+
+        fn bump(&mut self, counter: &mut i32, log: &mut String);
+    }
+
+    #[derive(Clone, Debug)]
+    struct Coord{
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug)]
+    struct Tile{
+        kind: i32,
+    }
+
+    struct Board{
+        tiles: Map<Coord, Tile>,
+    }
+
+    enum Terrain {
+        Grass,
+        Water,
+        Rock = 10,
+    }
+
+    struct Atlas{
+        base: Terrain,
+        costs: Map<Terrain, i32>,
+        samples: Map<Terrain, Tile>,
+        route: Vec<Terrain>,
+        layers: Map<i32, Terrain>,
+    }
+
+    impl Atlas{
+        fn next(&self, t: Terrain) -> Terrain;
+        #[iter(Item = Terrain, field = route)]
+        fn walk(&self);
+    }
+
     impl ConfigContainer{
-        #[iter(Item = Config)]
+        #[iter(Item = &Config)]
+        fn configs(&self);
+        #[iter(Item = &mut Config)]
+        fn configs_mut(&mut self);
+        #[iter(drain, Item = Config)]
         fn drain(&mut self);
     }
 
@@ -83,6 +179,182 @@ bind! {
         id:i32,
     }
 
+    #[derive(Debug)]
+    struct Secret{
+        id: i32,
+        #[protected]
+        code: i32,
+    }
+
+    #[derive(Debug)]
+    #[debug(protected)]
+    struct SecretVerbose{
+        id: i32,
+        #[protected]
+        code: i32,
+    }
+
+    #[contiguous(elem = f64, resize)]
+    struct SampleBuffer {}
+
+    #[derive(Debug)]
+    struct Signal {
+        samples: SampleBuffer,
+    }
+
+    impl Signal {
+        fn peek(&self) -> &SampleBuffer;
+        fn window(&mut self) -> &mut SampleBuffer;
+    }
+
+    struct Inventory {}
+
+    impl Inventory {
+        fn total(&self, items: &[Config]) -> i32;
+        fn total_kinds(&self, tiles: &[Tile]) -> i32;
+        fn total_vec(&self, items: &Vec<Config>) -> i32 = total;
+        fn join(&self, parts: &[&str], sep: &str) -> String;
+        fn count_names(&self, names: &[String]) -> i32;
+    }
+
+    #[derive(Debug)]
+    struct Node {
+        value: i32,
+    }
+
+    struct Tree {}
+
+    impl Tree {
+        #[iter(Item = &Node, field = nodes, double_ended)]
+        fn nodes(&self);
+        #[iter(Item = &mut Node)]
+        fn children_mut(&mut self) = children;
+        #[iter(Item = &Node, begin = nodes_begin, end = nodes_end)]
+        fn tail(&self);
+        #[iter(Item = &Node, range = children, begin = rbegin, end = rend)]
+        fn reversed(&self);
+        #[iter(drain, Item = Node, range = "obj.snapshot()")]
+        fn snapshot(&mut self);
+    }
+
+    struct Tags {}
+
+    impl Tags {
+        #[iter(Item = i32, field = scores, double_ended)]
+        fn scores(&self);
+        #[iter(Item = String, field = names)]
+        fn names(&self);
+        #[iter(Item = i32, range = "obj.doubled()")]
+        fn doubled(&self);
+    }
+
+    #[derive(Debug, Mirror)]
+    struct Account {
+        #[get = "getId", set = "setId"]
+        id: i32,
+        #[get = "getOwner", set = "setOwner"]
+        owner: String,
+        #[get = "getConfig", set = "setConfig"]
+        config: Config,
+        #[get = "getHistory"]
+        history: Vec<i32>,
+        #[rename = "type"]
+        kind: i32,
+    }
+
+    #[opaque]
+    struct Shape;
+
+    impl Shape {
+        fn square(side: f64) -> Self = make_square;
+        fn area(&self) -> f64;
+    }
+
+    #[no_ctor]
+    struct Ticket {
+        #[readonly]
+        number: i32,
+    }
+
+    struct Dispenser {}
+
+    impl Dispenser {
+        fn issue(&mut self) -> Ticket;
+        fn measure(&self, shape: &Shape) -> f64;
+        fn is_next(&self, ticket: &Ticket) -> bool;
+    }
+
+    #[derive(Debug, Init)]
+    struct Endpoint {
+        port: i32,
+        host: String,
+        timeout: Option<i32>,
+        label: Option<String>,
+        config: Config,
+        retries: Vec<i32>,
+    }
+
+    #[derive(Mirror, Serialize, Deserialize)]
+    struct Profile {
+        level: i32,
+        nickname: String,
+        #[readonly]
+        version: i32,
+        settings: Config,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Reservation {
+        seats: Option<i32>,
+        note: Option<String>,
+        config: Option<Config>,
+    }
+
+    // no getter for `owned`, so reflection leaves it out
+    struct PtrHolder {
+        id: i32,
+        owned: UniquePtr<Config>,
+    }
+
+    #[no_ctor]
+    struct Connection {
+        #[readonly]
+        host: String,
+    }
+
+    impl Connection {
+        fn open(host: &str) -> Result<Self>;
+        fn try_open(host: &str) -> Option<Self>;
+        fn find(id: i32) -> Result<Self> = find_connection;
+        fn lookup(id: i32) -> Option<Self> = find_connection;
+    }
+
+    struct Registry {
+        id: i32,
+    }
+
+    impl Registry {
+        fn new() -> Self;
+        #[protected]
+        fn with_nodes(id: i32, count: i32) -> Self;
+        fn open(id: i32) -> Self = open_registry;
+        #[protected]
+        fn seeded(id: i32) -> Self = seeded;
+        #[protected]
+        fn instances() -> i32;
+        #[protected]
+        fn call(&self, x: i32) -> i32 = "operator()";
+        #[protected]
+        #[iter(Item = &Node)]
+        fn iter(&self);
+        #[protected]
+        #[iter(Item = &mut Node, field = nodes)]
+        fn nodes_mut(&mut self);
+        #[protected]
+        #[iter(Item = &Node, range = "obj.all_nodes()", begin = crbegin, end = crend)]
+        fn nodes_rev(&self);
+    }
 }
 
 pub mod test;
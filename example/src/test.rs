@@ -188,6 +188,238 @@ mod tests {
         assert_eq!(total_id, 300);
     }
 
+    #[test]
+    fn test_borrowing_iterator() {
+        let mut container = ConfigContainer::new();
+        let ids: Vec<i32> = container.configs().map(|c| c.id()).collect();
+        assert_eq!(ids, [100, 200]);
+        // nothing was moved out
+        assert!(container.configs().all(|c| c.name() == "test"));
+
+        for mut config in container.configs_mut() {
+            config.set_id(config.id() + 1);
+        }
+        assert_eq!(container.as_ref().data().get(1).unwrap().id(), 201);
+
+        let view = container.as_ref();
+        let first = view.configs().next().unwrap();
+        assert_eq!(first.id(), 101);
+    }
+
+    #[test]
+    fn test_iterator_ranges() {
+        let mut tree = Tree::new();
+        let values = |it: &mut dyn Iterator<Item = i32>| it.collect::<Vec<_>>();
+
+        assert_eq!(values(&mut tree.nodes().map(|n| n.value())), [1, 2, 3]);
+        assert_eq!(values(&mut tree.nodes().rev().map(|n| n.value())), [3, 2, 1]);
+        let mut both = tree.nodes();
+        assert_eq!(both.next().unwrap().value(), 1);
+        assert_eq!(both.next_back().unwrap().value(), 3);
+        assert_eq!(both.next().unwrap().value(), 2);
+        assert!(both.next_back().is_none());
+
+        for mut node in tree.children_mut() {
+            node.set_value(node.value() * 10);
+        }
+        assert_eq!(values(&mut tree.tail().map(|n| n.value())), [20, 30]);
+        assert_eq!(values(&mut tree.reversed().map(|n| n.value())), [30, 20, 10]);
+
+        // draining a copy leaves the tree untouched
+        assert_eq!(values(&mut tree.snapshot().map(|n| n.value())), [10, 20, 30]);
+        assert_eq!(tree.nodes().count(), 3);
+    }
+
+    #[test]
+    fn test_copying_iterators() {
+        let tags = Tags::new();
+        assert_eq!(tags.scores().collect::<Vec<_>>(), [3, 1, 2]);
+        assert_eq!(tags.scores().rev().collect::<Vec<_>>(), [2, 1, 3]);
+        assert_eq!(tags.names().collect::<Vec<_>>(), ["a", "b"]);
+        // copies may come from a range returned by value
+        assert_eq!(tags.doubled().sum::<i32>(), 12);
+        assert_eq!(tags.scores().count(), 3);
+
+        let atlas = Atlas::new();
+        assert_eq!(atlas.walk().collect::<Vec<_>>(), [Terrain::Grass, Terrain::Rock]);
+    }
+
+    /// Compiles `snippet` after the generated header, returning the compiler
+    /// diagnostics if it is rejected.
+    fn cpp_rejects(snippet: &str) -> Option<String> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let out_dir = env!("OUT_DIR");
+        let cxx = std::env::var("CXX").unwrap_or_else(|_| "c++".to_string());
+        let mut child = Command::new(cxx)
+            .args(["-std=c++17", "-fsyntax-only", "-x", "c++", "-"])
+            .arg(format!("-I{}", out_dir))
+            .arg(format!("-I{}/cxxbridge/include", out_dir))
+            .arg(concat!("-I", env!("CARGO_MANIFEST_DIR"), "/src/cpp"))
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("no C++ compiler");
+        let source = format!("#include \"justcxx.hh\"\n{}\n", snippet);
+        child.stdin.take().unwrap().write_all(source.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        (!output.status.success()).then(|| String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+
+    #[test]
+    fn test_iterator_rejects_temporary_range() {
+        // borrowed items would outlive a range the iterator holds by value
+        let errors = cpp_rejects("DEFINE_ITER(Tree, snap_ref, (obj.snapshot()), cbegin, cend)")
+            .expect("borrowing a temporary range compiled");
+        assert!(errors.contains("Borrowing #[iter] Tree::snap_ref"), "{}", errors);
+        let errors = cpp_rejects("DEFINE_ITER_MUT(Tree, snap_mut, (obj.snapshot()), begin, end)");
+        assert!(errors.is_some());
+
+        assert_eq!(cpp_rejects("DEFINE_ITER(Tree, nodes_ref, (obj.nodes), cbegin, cend)"), None);
+        assert_eq!(cpp_rejects("DEFINE_ITER_DRAIN(Tree, snap_drain, (obj.snapshot()), begin, end)"), None);
+    }
+
+    #[test]
+    fn test_field_accessors() {
+        let mut account = Account::new();
+        assert_eq!(account.id(), 1);
+        account.set_id(9);
+        assert_eq!(account.id(), 9);
+
+        account.set_owner("alice");
+        assert_eq!(account.owner(), "alice");
+
+        let mut config = Config::new();
+        config.set_id(5);
+        account.as_mut().set_config(config);
+        assert_eq!(account.as_ref().config().id(), 5);
+        assert_eq!(account.as_ref().history().to_vec(), [1, 2]);
+
+        assert_eq!(account.kind(), 3);
+        account.set_kind(4);
+        assert_eq!(account.kind(), 4);
+        assert!(format!("{:?}", account).contains("kind: 4"));
+    }
+
+    #[test]
+    fn test_opaque_classes() {
+        let square = Shape::square(3.0);
+        assert_eq!(square.area(), 9.0);
+
+        let mut dispenser = Dispenser::new();
+        assert_eq!(dispenser.measure(square.as_ref()), 9.0);
+        let first = dispenser.issue();
+        let second = dispenser.issue();
+        assert_eq!((first.number(), second.number()), (1, 2));
+        assert!(dispenser.is_next(second.as_ref()));
+        assert!(!dispenser.is_next(first.as_ref()));
+    }
+
+    #[test]
+    fn test_from_init() {
+        let mut config = Config::new();
+        config.set_id(7);
+        let endpoint = Endpoint::from_init(EndpointInit {
+            port: 8080,
+            host: "localhost".to_string(),
+            timeout: Some(30),
+            config: Some(config),
+            ..Default::default()
+        });
+        assert_eq!(endpoint.port(), 8080);
+        assert_eq!(endpoint.host(), "localhost");
+        assert_eq!(endpoint.timeout(), Some(30));
+        assert_eq!(endpoint.label(), None);
+        assert_eq!(endpoint.as_ref().config().id(), 7);
+        assert!(endpoint.as_ref().retries().is_empty());
+
+        let defaults = Endpoint::from_init(EndpointInit {
+            label: Some("edge".to_string()),
+            ..Default::default()
+        });
+        assert_eq!((defaults.port(), defaults.host()), (0, String::new()));
+        assert_eq!(defaults.label().as_deref(), Some("edge"));
+        assert_eq!(defaults.as_ref().config().id(), 42);
+    }
+
+    #[test]
+    fn test_mirror() {
+        let mut profile = Profile::new();
+        let mut data = profile.as_ref().snapshot();
+        assert_eq!(
+            data,
+            ProfileData {
+                level: 1,
+                nickname: "anon".to_string(),
+                version: 5,
+                settings: ConfigData {
+                    id: 42,
+                    value: 56.0,
+                    name: "test".to_string(),
+                },
+            }
+        );
+
+        data.level = 3;
+        data.nickname = "neo".to_string();
+        data.version = 9;
+        data.settings.id = 7;
+        profile.apply(&data);
+        assert_eq!(profile.level(), 3);
+        assert_eq!(profile.nickname(), "neo");
+        assert_eq!(profile.version(), 5); // read-only fields are not written back
+        assert_eq!(profile.as_ref().settings().id(), 7);
+
+        let mut account = Account::new();
+        let mut snapshot = account.snapshot();
+        assert_eq!((snapshot.id, snapshot.kind), (1, 3));
+        snapshot.owner = "bob".to_string();
+        account.apply(&snapshot);
+        assert_eq!(account.owner(), "bob");
+    }
+
+    #[test]
+    fn test_fallible_ctors() {
+        assert_eq!(Connection::open("db").unwrap().host(), "db");
+        let err = Connection::open("").unwrap_err();
+        assert_eq!(err.what(), "empty host");
+
+        assert!(Connection::try_open("db").is_some());
+        assert!(Connection::try_open("").is_none());
+
+        assert_eq!(Connection::find(2).unwrap().host(), "host2");
+        assert_eq!(Connection::find(-1).unwrap_err().what(), "negative id");
+        assert!(Connection::find(0).unwrap_err().what().contains("returned null"));
+
+        assert_eq!(Connection::lookup(3).unwrap().host(), "host3");
+        assert!(Connection::lookup(0).is_none());
+        assert!(Connection::lookup(-1).is_none());
+    }
+
+    #[test]
+    fn test_exposer_members() {
+        let values = |r: &justcxx::CppOwned<Registry>| r.iter().map(|n| n.value()).collect::<Vec<_>>();
+
+        let mut reg = Registry::with_nodes(4, 3);
+        assert_eq!(values(&reg), [40, 41, 42]);
+        for mut node in reg.nodes_mut() {
+            node.set_value(node.value() + 1);
+        }
+        assert_eq!(values(&reg), [41, 42, 43]);
+        assert_eq!(reg.nodes_rev().map(|n| n.value()).collect::<Vec<_>>(), [43, 42, 41]);
+        assert_eq!(reg.call(1), 5);
+        assert_eq!(Registry::instances(), 3);
+
+        let opened = Registry::open(7);
+        assert_eq!(opened.id(), 7);
+        assert_eq!(opened.iter().count(), 0);
+
+        let seeded = Registry::seeded(2);
+        assert_eq!(values(&seeded), [20, 21]);
+        assert_eq!(Registry::new().call(2), 2);
+    }
+
     #[test]
     fn test_vec_obj() {
         let container = ConfigContainer::new();
@@ -223,6 +455,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_fold)]
     fn test_vec_number() {
         let container = ConfigContainer::new();
         let mut vec = container.as_mut().ids();
@@ -248,6 +481,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_option_obj() {
         let mut wallet = Wallet::new();
         assert_eq!(wallet.as_ref().config().is_none(), true);
@@ -280,4 +514,878 @@ mod tests {
         assert_eq!(str_config_map.len(), 1);
         assert_eq!(str_config_map.get("one").unwrap().id(), 30);
     }
+
+    #[test]
+    fn test_clone_obj() {
+        let mut config = Config::new();
+        config.set_id(7);
+        let mut copy = config.clone();
+        copy.set_id(8);
+        assert_eq!(config.id(), 7);
+        assert_eq!(copy.id(), 8);
+        assert_eq!(copy.name(), "test");
+    }
+
+    #[test]
+    fn test_ref_to_owned() {
+        let manager = Manager::new();
+        let mut config = manager.as_ref().config().to_owned();
+        config.set_id(99);
+        assert_eq!(manager.as_ref().config().id(), 42);
+        assert_eq!(config.id(), 99);
+    }
+
+    #[test]
+    fn test_clone_containers() {
+        let container = ConfigContainer::new();
+        let data = container.as_ref().data().to_owned();
+        data.as_mut().get_mut(0).unwrap().set_id(1);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.as_ref().get(0).unwrap().id(), 1);
+        assert_eq!(container.as_ref().data().get(0).unwrap().id(), 100);
+
+        let map_example = MapExample::new();
+        let map = map_example.as_ref().int_config_map().to_owned();
+        let map_copy = map.clone();
+        assert_eq!(map_copy.len(), 2);
+        assert_eq!(map_copy.get(20).unwrap().id(), 20);
+    }
+
+    #[test]
+    fn test_debug_fields() {
+        let manager = Manager::new();
+        assert_eq!(
+            format!("{:?}", manager),
+            r#"Manager { config: Config { id: 42, value: 56.0, name: "test" } }"#
+        );
+
+        let wallet = Wallet::new();
+        assert_eq!(format!("{:?}", wallet), "Wallet { config: None }");
+    }
+
+    #[test]
+    fn test_debug_containers() {
+        let container = ConfigContainer::new();
+        let debug = format!("{:?}", container.as_ref());
+        assert!(debug.starts_with("ConfigContainer { data: [Config { id: 100,"));
+        assert!(debug.ends_with(r#"ids: [], names: ["100", "200"], flags: [true, false, true] }"#));
+
+        let map_example = MapExample::new();
+        assert_eq!(
+            format!("{:?}", map_example.as_ref().str_config_map()),
+            r#"{"one": Config { id: 30, value: 56.0, name: "test" }}"#
+        );
+    }
+
+    #[test]
+    fn test_debug_protected() {
+        assert_eq!(format!("{:?}", Secret::new()), "Secret { id: 1 }");
+        assert_eq!(
+            format!("{:?}", SecretVerbose::new()),
+            "SecretVerbose { id: 2, code: 9 }"
+        );
+        assert!(format!("{:?}", Ctor::new()).starts_with("CppObject(0x"));
+    }
+
+    #[test]
+    fn test_display() {
+        let manager = Manager::new();
+        assert_eq!(manager.as_ref().config().to_string(), "42 56 test");
+        assert_eq!(format!("[{:>12}]", Config::new()), "[  42 56 test]");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let config: justcxx::CppOwned<Config> = "7 1.5 parsed".parse().unwrap();
+        assert_eq!(config.id(), 7);
+        assert_eq!(config.value(), 1.5);
+        assert_eq!(config.name(), "parsed");
+
+        let err = "not-a-number".parse::<justcxx::CppOwned<Config>>();
+        assert_eq!(err.unwrap_err().what(), "failed to parse Config");
+        assert!("7 1.5 parsed extra".parse::<justcxx::CppOwned<Config>>().is_err());
+        assert!("7 1.5 parsed \n".parse::<justcxx::CppOwned<Config>>().is_ok());
+    }
+
+    #[test]
+    fn test_method_return_self() {
+        let config = Config::new();
+        let other = config.with_id(5);
+        assert_eq!(other.id(), 5);
+        assert_eq!(config.id(), 42);
+        assert!(!config.same_id(other.as_ref()));
+        assert!(config.same_id(config.with_id(42).as_ref()));
+    }
+
+    #[test]
+    fn test_method_return_mut_self() {
+        let mut config = Config::new();
+        config.rename("first").rename("second");
+        assert_eq!(config.name(), "second");
+    }
+
+    #[test]
+    fn test_consuming_method() {
+        let mut builder = Builder::new();
+        builder.with_id(3);
+        let config = builder.build();
+        assert_eq!(config.id(), 3);
+        assert_eq!(config.name(), "built");
+
+        let mut builder = Builder::new();
+        builder.set_id(4);
+        assert_eq!(builder.finish(10), 14);
+    }
+
+    #[test]
+    fn test_default_args() {
+        let canvas = Canvas::new(5);
+        assert_eq!(canvas.w(), 5);
+        assert_eq!(canvas.h(), 10);
+
+        let canvas = Canvas::new_with(5, CanvasNewArgs { h: 20 });
+        assert_eq!(canvas.h(), 20);
+
+        let mut canvas = Canvas::new(1);
+        canvas.resize(2, 3);
+        assert_eq!((canvas.w(), canvas.h()), (2, 3));
+        assert!(canvas.keep());
+        assert_eq!(canvas.label(), "resized");
+
+        canvas.resize_with(
+            4,
+            5,
+            CanvasResizeArgs {
+                keep: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!((canvas.w(), canvas.h()), (4, 5));
+        assert!(!canvas.keep());
+        assert_eq!(canvas.label(), "resized");
+    }
+
+    #[test]
+    fn test_out_params() {
+        let mut parser = Parser::new();
+        parser.set_base(100);
+
+        assert_eq!(parser.try_parse("23"), (true, 123, String::new()));
+        let (ok, _, err) = parser.try_parse("abc");
+        assert!(!ok);
+        assert_eq!(err, "not a number: abc");
+
+        assert_eq!(parser.split(42), (4, 2));
+    }
+
+    #[test]
+    fn test_in_out_params() {
+        let mut parser = Parser::new();
+        let mut counter = 1;
+        let mut log = String::from("start");
+        parser.bump(&mut counter, &mut log);
+        parser.bump(&mut counter, &mut log);
+        assert_eq!(counter, 3);
+        assert_eq!(log, "start++");
+    }
+
+    #[test]
+    fn test_vec_number_mutation() {
+        let mut vec = CppVector::<i32>::new();
+        vec.extend([1, 2, 3, 4, 5]);
+        assert_eq!(vec.first(), Some(1));
+        assert_eq!(vec.last(), Some(5));
+
+        assert_eq!(vec.pop(), Some(5));
+        vec.insert(0, 0);
+        assert_eq!(vec.as_slice(), &[0, 1, 2, 3, 4]);
+        assert_eq!(vec.remove(1), 1);
+        assert_eq!(vec.swap_remove(0), 0);
+        assert_eq!(vec.as_slice(), &[4, 2, 3]);
+
+        vec.swap(0, 2);
+        vec.retain(|x| *x != 2);
+        assert_eq!(vec.as_slice(), &[3, 4]);
+
+        vec.resize(4, 9);
+        assert_eq!(vec.as_slice(), &[3, 4, 9, 9]);
+        vec.truncate(1);
+        assert_eq!(vec.as_slice(), &[3]);
+
+        vec.reserve(100);
+        assert!(vec.capacity() >= 101);
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(vec.pop(), None);
+        assert_eq!(vec.first(), None);
+    }
+
+    #[test]
+    fn test_vec_string_mutation() {
+        let mut vec = CppVector::<String>::new();
+        vec.extend(["a", "b", "c"]);
+        vec.insert(1, "x");
+        assert_eq!(vec.remove(0), "a");
+        assert_eq!(vec.pop(), Some("c".to_string()));
+        vec.resize(4, "z");
+        vec.retain(|s| s != "b");
+        assert_eq!(vec.iter().collect::<Vec<_>>(), ["x", "z", "z"]);
+        assert_eq!(vec.last(), Some("z".to_string()));
+
+        let mut n = 0;
+        vec.resize_with(5, || {
+            n += 1;
+            n.to_string()
+        });
+        assert_eq!(vec.iter().collect::<Vec<_>>(), ["x", "z", "z", "1", "2"]);
+        vec.resize_with(1, String::new);
+        assert_eq!(vec.iter().collect::<Vec<_>>(), ["x"]);
+    }
+
+    #[test]
+    fn test_vec_bool() {
+        let container = ConfigContainer::new();
+        let mut flags = container.as_mut().flags();
+        assert_eq!(flags, vec![true, false, true]);
+        assert_eq!(flags.get(1), Some(false));
+        assert_eq!(flags.get(3), None);
+        assert!(flags[0]);
+
+        flags.set(1, true);
+        flags.push(false);
+        flags.swap(0, 3);
+        assert_eq!(flags.to_vec(), [false, true, true, true]);
+        assert_eq!(flags.iter().rev().collect::<Vec<_>>(), [true, true, true, false]);
+
+        flags.insert(0, false);
+        assert!(!flags.remove(1));
+        assert_eq!(flags.pop(), Some(true));
+        flags.retain(|b| *b);
+        assert_eq!(flags.len(), 2);
+
+        let owned: justcxx::CppOwned<CppVector<bool>> = [true, false].iter().copied().collect();
+        assert_eq!(owned.into_vec(), [true, false]);
+    }
+
+    #[test]
+    fn test_contiguous() {
+        let signal = Signal::new();
+        assert_eq!(signal.as_ref().samples(), &[1.5, 0.0, 0.0]);
+
+        signal.as_mut().samples_mut()[1] = 2.5;
+        signal.as_mut().window()[2] = 3.5;
+        assert_eq!(signal.as_ref().peek(), &[1.5, 2.5, 3.5]);
+        assert_eq!(format!("{:?}", signal), "Signal { samples: [1.5, 2.5, 3.5] }");
+
+        let mut buf = SampleBuffer::new();
+        assert!(buf.is_empty());
+        buf.resize(2);
+        buf.as_mut_slice().copy_from_slice(&[4.0, 5.0]);
+        buf[0] += 1.0;
+        assert_eq!(buf.as_slice(), &[5.0, 5.0]);
+        assert_eq!(buf.iter().sum::<f64>(), 10.0);
+        assert_eq!(format!("{:?}", buf), "[5.0, 5.0]");
+    }
+
+    #[test]
+    fn test_slice_args() {
+        let inventory = Inventory::new();
+        let mut a = Config::new();
+        a.set_id(1);
+        let mut b = Config::new();
+        b.set_id(2);
+        assert_eq!(inventory.total(&[a.as_ref(), b.as_ref()]), 3);
+        assert_eq!(inventory.total(&[]), 0);
+
+        // copied through the C++ copy constructor, no #[derive(Clone)] needed
+        let mut tile = Tile::new();
+        tile.set_kind(4);
+        assert_eq!(inventory.total_kinds(&[tile.as_ref(), tile.as_ref()]), 8);
+
+        let container = ConfigContainer::new();
+        assert_eq!(inventory.total_vec(container.as_ref().data()), 300);
+
+        assert_eq!(inventory.join(&["a", "b", "c"], "-"), "a-b-c");
+        assert_eq!(inventory.count_names(&["x", "y"]), 2);
+    }
+
+    #[test]
+    fn test_vec_obj_mutation() {
+        let container = ConfigContainer::new();
+        let mut vec = container.as_mut().data();
+
+        let mut config = Config::new();
+        config.set_id(7);
+        vec.insert(0, config);
+        assert_eq!(vec.first().unwrap().id(), 7);
+
+        let mut config = Config::new();
+        config.set_id(8);
+        vec.set(1, config);
+        assert_eq!(vec.get(1).unwrap().id(), 8);
+
+        let removed = vec.swap_remove(0);
+        assert_eq!(removed.id(), 7);
+        assert_eq!(vec.first().unwrap().id(), 200);
+
+        vec.resize_with(4, Config::new);
+        assert_eq!(vec.len(), 4);
+        vec.retain(|c| c.id() != 200);
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.pop().unwrap().id(), vec.last().unwrap().id());
+
+        vec.extend([Config::new(), Config::new()]);
+        assert_eq!(vec.len(), 4);
+
+        let mut filler = Config::new();
+        filler.set_id(42);
+        vec.resize(6, filler.as_ref());
+        assert_eq!(vec.len(), 6);
+        assert_eq!(vec.get(4).unwrap().id(), 42);
+        assert_eq!(vec.get(5).unwrap().id(), 42);
+        vec.get_mut(4).unwrap().set_id(43);
+        assert_eq!(vec.get(5).unwrap().id(), 42);
+        assert_eq!(filler.id(), 42);
+        vec.resize(2, filler.as_ref());
+        assert_eq!(vec.len(), 2);
+        vec.clear();
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn test_map_mutation() {
+        let mut map = CppMap::<i32, String>::new();
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.insert(1, "uno"), Some("one".to_string()));
+        map.insert(2, "two");
+        assert!(map.contains_key(2));
+        assert_eq!(map.remove(2), Some("two".to_string()));
+        assert_eq!(map.remove(2), None);
+        assert!(!map.contains_key(2));
+
+        assert_eq!(map.entry(3).or_insert_with(|| "three".to_string()), "three");
+        assert_eq!(map.entry(3).or_insert("tres".to_string()), "three");
+
+        let mut keys: Vec<i32> = map.keys().collect();
+        keys.sort();
+        assert_eq!(keys, [1, 3]);
+
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_map_primitive_get_mut() {
+        let mut map = CppMap::<String, i32>::new();
+        *map.entry("hits").or_insert(0) += 1;
+        *map.entry("hits").or_insert(0) += 1;
+        *map.get_mut("hits").unwrap() += 10;
+        assert_eq!(map.get("hits"), Some(12));
+        assert!(map.get_mut("misses").is_none());
+        assert_eq!(map.values().sum::<i32>(), 12);
+        assert_eq!(map.insert("hits", 1), Some(12));
+        assert_eq!(map.insert("misses", 0), None);
+
+        for (key, val) in map.iter_mut() {
+            *val += key.len() as i32;
+        }
+        for val in &mut map {
+            *val.1 *= 10;
+        }
+        assert_eq!((map.get("hits"), map.get("misses")), (Some(50), Some(60)));
+        assert_eq!(map.values_mut().map(|v| *v).sum::<i32>(), 110);
+
+        let mut names = CppMap::<i32, String>::new();
+        names.insert(1, "one");
+        names.insert(2, "two");
+        names.get_mut(1).unwrap().push_str("!");
+        for (key, mut name) in names.iter_mut() {
+            name.as_mut().push_str(&key.to_string());
+        }
+        assert_eq!(names.get(1), Some("one!1".to_string()));
+        assert_eq!(names.get(2), Some("two2".to_string()));
+        assert!(names.get_mut(3).is_none());
+    }
+
+    #[test]
+    fn test_map_obj_mutation() {
+        let example = MapExample::new();
+        let mut map = example.as_mut().int_config_map();
+
+        let mut config = Config::new();
+        config.set_id(30);
+        assert!(map.insert(30, config).is_none());
+        assert_eq!(map.get(30).unwrap().id(), 30);
+        assert_eq!(map.insert(30, Config::new()).unwrap().id(), 30);
+        assert_eq!(map.get(30).unwrap().id(), 42);
+
+        map.get_mut(10).unwrap().set_id(11);
+        assert_eq!(map.get(10).unwrap().id(), 11);
+
+        for (key, mut config) in map.iter_mut() {
+            config.set_id(key * 2);
+        }
+        for mut config in map.values_mut() {
+            config.set_id(config.id() + 1);
+        }
+        assert_eq!(map.get(20).unwrap().id(), 41);
+
+        let removed = map.remove(20).unwrap();
+        assert_eq!(removed.id(), 41);
+        assert_eq!(map.len(), 2);
+
+        let mut entry = map.entry(40).or_insert_with(Config::new);
+        entry.set_id(40);
+        assert_eq!(map.get(40).unwrap().id(), 40);
+        assert_eq!(map.entry(40).or_insert_with(Config::new).id(), 40);
+    }
+
+    fn coord(x: i32, y: i32) -> justcxx::CppOwned<Coord> {
+        let mut c = Coord::new();
+        c.set_x(x);
+        c.set_y(y);
+        c
+    }
+
+    #[test]
+    fn test_map_object_keys() {
+        let board = Board::new();
+        let mut tiles = board.as_mut().tiles();
+
+        let key = coord(1, 2);
+        assert_eq!(tiles.get(key.as_ref()).unwrap().kind(), 5);
+        assert!(!tiles.contains_key(coord(3, 4).as_ref()));
+
+        let mut tile = Tile::new();
+        tile.set_kind(7);
+        tiles.insert(coord(3, 4).as_ref(), tile);
+        tiles.get_mut(key.as_ref()).unwrap().set_kind(6);
+
+        let mut seen: Vec<(i32, i32, i32)> = tiles
+            .iter()
+            .map(|(k, v)| (k.x(), k.y(), v.kind()))
+            .collect();
+        seen.sort();
+        assert_eq!(seen, [(1, 2, 6), (3, 4, 7)]);
+
+        let far = coord(9, 9);
+        tiles.entry(far.as_ref()).or_insert_with(Tile::new).set_kind(1);
+        assert_eq!(tiles.remove(far.as_ref()).unwrap().kind(), 1);
+        assert_eq!(tiles.len(), 2);
+    }
+
+    #[test]
+    fn test_enum_map_keys() {
+        use justcxx::{Reflect, Value};
+
+        let mut atlas = Atlas::new();
+        assert_eq!(atlas.base(), Terrain::Grass);
+        atlas.set_base(Terrain::Rock);
+        assert_eq!(atlas.base().repr, 10);
+        assert_eq!(atlas.next(Terrain::Grass), Terrain::Water);
+
+        let mut costs = atlas.as_mut().costs();
+        assert_eq!(costs.get(Terrain::Water), Some(3));
+        assert_eq!(costs.insert(Terrain::Rock, 9), None);
+        *costs.get_mut(Terrain::Grass).unwrap() += 1;
+        let mut seen: Vec<_> = costs.iter().collect();
+        seen.sort();
+        assert_eq!(seen, [(Terrain::Grass, 2), (Terrain::Water, 3), (Terrain::Rock, 9)]);
+
+        let mut samples = atlas.as_mut().samples();
+        samples.entry(Terrain::Water).or_insert_with(Tile::new).set_kind(4);
+        assert_eq!(samples.get(Terrain::Water).unwrap().kind(), 4);
+        assert!(samples.remove(Terrain::Grass).is_none());
+
+        assert_eq!(atlas.get_field("base"), Some(Value::String("Rock".into())));
+        atlas.set_field("base", Value::String("Water".into())).unwrap();
+        assert_eq!(atlas.base(), Terrain::Water);
+        assert!(atlas.set_field("base", Value::String("Lava".into())).is_err());
+    }
+
+    #[test]
+    fn test_enum_containers() {
+        let atlas = Atlas::new();
+
+        let mut route = atlas.as_mut().route();
+        assert_eq!(route, vec![Terrain::Grass, Terrain::Rock]);
+        route.push(Terrain::Water);
+        route[0] = Terrain::Water;
+        assert_eq!(route.pop(), Some(Terrain::Water));
+        assert_eq!(route.remove(0), Terrain::Water);
+        route.resize(3, Terrain::Grass);
+        assert_eq!(route.as_slice(), [Terrain::Rock, Terrain::Grass, Terrain::Grass]);
+        assert_eq!(route.swap_remove(0), Terrain::Rock);
+        route.as_mut_slice().sort();
+        assert_eq!(route.iter().filter(|t| **t == Terrain::Grass).count(), 2);
+
+        let mut layers = atlas.as_mut().layers();
+        assert_eq!(layers.get(0), Some(Terrain::Water));
+        assert_eq!(layers.insert(0, Terrain::Rock), Some(Terrain::Water));
+        assert_eq!(layers.insert(1, Terrain::Grass), None);
+        *layers.get_mut(1).unwrap() = Terrain::Water;
+        *layers.entry(2).or_insert(Terrain::Grass) = Terrain::Rock;
+        assert_eq!(layers.remove(1), Some(Terrain::Water));
+        let mut seen: Vec<_> = layers.iter().collect();
+        seen.sort();
+        assert_eq!(seen, [(0, Terrain::Rock), (2, Terrain::Rock)]);
+        assert_eq!(Terrain::default(), Terrain::Grass);
+    }
+
+    #[test]
+    fn test_vec_bulk_conversions() {
+        let mut vec = justcxx::CppOwned::<CppVector<i32>>::from(&[1, 2, 3][..]);
+        Extend::extend(&mut vec, &[4, 5]);
+        assert_eq!(vec.to_vec(), [1, 2, 3, 4, 5]);
+
+        let doubled: justcxx::CppOwned<CppVector<i32>> = vec.iter().map(|x| x * 2).collect();
+        assert_eq!(doubled.into_vec(), [2, 4, 6, 8, 10]);
+
+        let mut names = justcxx::CppOwned::<CppVector<String>>::from(&["a", "b"][..]);
+        names.extend(vec!["c".to_string()]);
+        assert_eq!(names.to_vec(), ["a", "b", "c"]);
+        assert_eq!(names.get(1), Some("b".to_string()));
+
+        let collected: justcxx::CppOwned<CppVector<String>> = ["x", "y"].into_iter().collect();
+        assert_eq!(collected.into_vec(), ["x", "y"]);
+    }
+
+    #[test]
+    fn test_vec_obj_collect() {
+        let configs: justcxx::CppOwned<CppVector<Config>> = (1..=3)
+            .map(|id| {
+                let mut c = Config::new();
+                c.set_id(id);
+                c
+            })
+            .collect();
+        assert_eq!(configs.len(), 3);
+
+        let ids: Vec<i32> = configs.into_vec().iter().map(|c| c.id()).collect();
+        assert_eq!(ids, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_map_bulk_conversions() {
+        let mut source = std::collections::HashMap::new();
+        source.insert(1, "one".to_string());
+        source.insert(2, "two".to_string());
+
+        let map = justcxx::CppOwned::<CppMap<i32, String>>::from(source.clone());
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(2), Some("two".to_string()));
+        assert_eq!(map.to_hash_map(), source);
+    }
+
+    fn total<C: justcxx::CppContainer>(containers: &[&C]) -> usize {
+        containers.iter().map(|c| c.len()).sum()
+    }
+
+    #[test]
+    fn test_vec_std_traits() {
+        let mut vec: justcxx::CppOwned<CppVector<i32>> = (1..=4).collect();
+        vec[0] = 10;
+        vec[1..3].copy_from_slice(&[20, 30]);
+        assert_eq!(vec[3], 4);
+        assert!(vec == [10, 20, 30, 4][..]);
+        assert!(vec == vec![10, 20, 30, 4]);
+
+        for x in &mut vec {
+            *x += 1;
+        }
+        let mut seen = Vec::new();
+        for x in &vec {
+            seen.push(*x);
+        }
+        assert_eq!(seen, [11, 21, 31, 5]);
+
+        let iter = vec.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(vec.iter().next_back(), Some(&5));
+
+        let names: justcxx::CppOwned<CppVector<String>> = ["a", "b"].into_iter().collect();
+        assert!(names == ["a", "b"][..]);
+        assert_eq!(names.iter().rev().collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!((&names).into_iter().len(), 2);
+
+        assert_eq!(total(&[&vec, &vec]), 8);
+        assert!(!justcxx::CppContainer::is_empty(&names));
+        assert_eq!(vec.into_iter().sum::<i32>(), 68);
+    }
+
+    #[test]
+    fn test_vec_obj_std_traits() {
+        let container = ConfigContainer::new();
+        let data = container.as_ref().data();
+
+        let ids: Vec<i32> = data.into_iter().map(|c| c.id()).collect();
+        assert_eq!(ids, [100, 200]);
+
+        let mut iter = data.iter();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next_back().unwrap().id(), 200);
+        assert_eq!(iter.next().unwrap().id(), 100);
+        assert!(iter.next().is_none());
+
+        for mut config in container.as_mut().data() {
+            config.set_id(config.id() + 1);
+        }
+        let mut data = container.as_mut().data();
+        for mut config in &mut data {
+            config.set_id(config.id() * 2);
+        }
+        let ids: Vec<i32> = (&data).into_iter().map(|c| c.id()).collect();
+        assert_eq!(ids, [202, 402]);
+    }
+
+    #[test]
+    fn test_map_std_traits() {
+        let mut map: justcxx::CppOwned<CppMap<i32, String>> =
+            [(1, "one".to_string())].into_iter().collect();
+        map.extend([(2, "two".to_string())]);
+        assert_eq!(map.iter().len(), 2);
+        assert_eq!(justcxx::CppContainer::len(&map), 2);
+
+        let mut keys: Vec<i32> = (&map).into_iter().map(|(k, _)| k).collect();
+        keys.sort();
+        assert_eq!(keys, [1, 2]);
+
+        let example = MapExample::new();
+        for (key, mut config) in example.as_mut().int_config_map() {
+            config.set_id(key + 1);
+        }
+        let mut ids: Vec<i32> = example
+            .as_ref()
+            .int_config_map()
+            .into_iter()
+            .map(|(_, c)| c.id())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, [11, 21]);
+    }
+
+    #[test]
+    fn test_serde() {
+        use serde_json::json;
+
+        let manager = Manager::new();
+        assert_eq!(
+            serde_json::to_value(&manager).unwrap(),
+            json!({ "config": { "id": 42, "value": 56.0, "name": "test" } })
+        );
+        let chance = Chance::new();
+        assert_eq!(serde_json::to_value(&chance).unwrap(), json!({ "probability": 75 }));
+        let wallet = Wallet::new();
+        assert_eq!(serde_json::to_value(&wallet).unwrap(), json!({ "config": null }));
+
+        let container = ConfigContainer::new();
+        let value = serde_json::to_value(&container).unwrap();
+        assert_eq!(value["ids"], json!([]));
+        assert_eq!(value["names"], json!(["100", "200"]));
+        assert_eq!(value["flags"], json!([true, false, true]));
+        assert_eq!(value["data"][1]["id"], json!(200));
+
+        let example = MapExample::new();
+        let value = serde_json::to_value(example.as_ref()).unwrap();
+        assert_eq!(value["int_str_map"], json!({ "1": "one", "2": "two" }));
+        assert_eq!(value["str_config_map"]["one"]["id"], json!(30));
+
+        let manager: justcxx::CppOwned<Manager> =
+            serde_json::from_str(r#"{ "config": { "id": 7, "name": "seven" } }"#).unwrap();
+        let config = manager.as_ref().config();
+        assert_eq!((config.id(), config.value(), config.name()), (7, 56.0, "seven".into()));
+
+        let container: justcxx::CppOwned<ConfigContainer> = serde_json::from_str(
+            r#"{ "data": [{ "id": 1 }], "ids": [4, 5], "names": ["x"], "flags": [false] }"#,
+        )
+        .unwrap();
+        let round_trip = serde_json::to_value(&container).unwrap();
+        assert_eq!(round_trip["data"][0]["id"], json!(1));
+        assert_eq!(round_trip["ids"], json!([4, 5]));
+        assert_eq!(round_trip["names"], json!(["x"]));
+        assert_eq!(round_trip["flags"], json!([false]));
+
+        let example: justcxx::CppOwned<MapExample> = serde_json::from_value(json!({
+            "int_str_map": { "3": "three" },
+            "str_config_map": { "a": { "id": 9 } },
+            "unknown": 1,
+        }))
+        .unwrap();
+        let example = example.as_ref();
+        assert_eq!(example.int_str_map().get(3), Some("three".to_string()));
+        assert_eq!(example.int_str_map().len(), 1);
+        assert_eq!(example.str_config_map().get("a").unwrap().id(), 9);
+        assert_eq!(example.int_config_map().len(), 2); // absent fields keep their value
+
+        let chance: justcxx::CppOwned<Chance> =
+            serde_json::from_str(r#"{ "probability": 10 }"#).unwrap();
+        assert_eq!(chance.probability(), Some(10));
+        let chance: justcxx::CppOwned<Chance> =
+            serde_json::from_str(r#"{ "probability": null }"#).unwrap();
+        assert_eq!(chance.probability(), None);
+        let round_trip: justcxx::CppOwned<Chance> =
+            serde_json::from_value(serde_json::to_value(&chance).unwrap()).unwrap();
+        assert_eq!(round_trip.probability(), None);
+
+        let reservation: justcxx::CppOwned<Reservation> =
+            serde_json::from_str(r#"{ "seats": null, "note": "aisle", "config": { "id": 8 } }"#)
+                .unwrap();
+        assert_eq!(reservation.seats(), None);
+        assert_eq!(reservation.note(), Some("aisle".to_string()));
+        assert_eq!(reservation.as_ref().config().unwrap().id(), 8);
+        assert_eq!(
+            serde_json::to_value(&reservation).unwrap()["config"]["name"],
+            json!("test")
+        );
+
+        assert!(serde_json::from_str::<justcxx::CppOwned<Config>>(r#"{ "id": "x" }"#).is_err());
+    }
+
+    #[test]
+    fn test_serde_seq() {
+        // bincode has no field names, so structs travel as sequences
+        let mut reservation = Reservation::new();
+        reservation.clear_seats();
+        reservation.set_note("aisle");
+        let bytes = bincode::serialize(&reservation).unwrap();
+        let copy: justcxx::CppOwned<Reservation> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(copy.seats(), None);
+        assert_eq!(copy.note(), Some("aisle".to_string()));
+        assert!(copy.as_ref().config().is_none());
+
+        // read-only fields are read past without being written back
+        let mut profile = Profile::new();
+        profile.set_level(4);
+        profile.as_mut().settings().set_id(9);
+        let bytes = bincode::serialize(&profile).unwrap();
+        let copy: justcxx::CppOwned<Profile> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(serde_json::to_value(&copy).unwrap(), serde_json::to_value(&profile).unwrap());
+
+        let container = ConfigContainer::new();
+        let bytes = bincode::serialize(&container).unwrap();
+        let copy: justcxx::CppOwned<ConfigContainer> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(serde_json::to_value(&copy).unwrap(), serde_json::to_value(&container).unwrap());
+
+        let example = MapExample::new();
+        let bytes = bincode::serialize(&example).unwrap();
+        let copy: justcxx::CppOwned<MapExample> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(copy.as_ref().int_str_map().get(2), Some("two".to_string()));
+
+        assert!(bincode::deserialize::<justcxx::CppOwned<Reservation>>(&bytes[..1]).is_err());
+    }
+
+    #[test]
+    fn test_reflect() {
+        use justcxx::{FieldError, FieldInfo, FromValue, Reflect, ToValue, Value};
+
+        assert_eq!(
+            Profile::FIELDS[2],
+            FieldInfo { name: "version", ty: "i32", readonly: true, protected: false }
+        );
+        assert_eq!(Profile::FIELDS[4].ty, "Vec<String>");
+        assert_eq!(Chance::FIELDS[0].ty, "Option<i32>");
+        assert!(Secret::FIELDS[1].protected);
+        assert!(Account::FIELDS.iter().any(|f| f.name == "history" && f.readonly));
+
+        let mut profile = Profile::new();
+        assert_eq!(profile.get_field("nickname"), Some(Value::String("anon".into())));
+        assert_eq!(profile.get_field("missing"), None);
+        assert_eq!(
+            profile.get_field("settings").unwrap(),
+            Value::Object(vec![
+                ("id".into(), Value::Int(42)),
+                ("value".into(), Value::Float(56.0)),
+                ("name".into(), Value::String("test".into())),
+            ])
+        );
+
+        profile.set_field("level", Value::Int(4)).unwrap();
+        profile.set_field("nickname", Value::String("neo".into())).unwrap();
+        profile
+            .set_field("tags", Value::List(vec![Value::String("a".into())]))
+            .unwrap();
+        profile
+            .set_field("settings", Value::Object(vec![("id".into(), Value::UInt(7))]))
+            .unwrap();
+        assert_eq!(profile.level(), 4);
+        assert_eq!(profile.nickname(), "neo");
+        assert_eq!(profile.as_ref().tags().get(0), Some("a".to_string()));
+        assert_eq!(profile.as_ref().settings().id(), 7);
+        assert_eq!(profile.as_ref().settings().name(), "test");
+
+        assert_eq!(profile.set_field("version", Value::Int(1)), Err(FieldError::ReadOnly));
+        assert_eq!(profile.set_field("level", Value::Bool(true)), Err(FieldError::TypeMismatch));
+        assert_eq!(profile.set_field("level", Value::Int(1 << 40)), Err(FieldError::TypeMismatch));
+        assert_eq!(profile.set_field("missing", Value::Null), Err(FieldError::NotFound));
+        assert_eq!(profile.as_ref().set_field("level", Value::Int(1)), Err(FieldError::ReadOnly));
+
+        // whole objects round-trip, read-only fields are skipped on the way back
+        let mut value = profile.to_value();
+        let Value::Object(entries) = &mut value else { unreachable!() };
+        entries[0].1 = Value::Int(9);
+        let mut other = Profile::new();
+        other.set_fields(value).unwrap();
+        assert_eq!((other.level(), other.nickname()), (9, "neo".to_string()));
+
+        let mut account = Account::new();
+        let config = account.get_field("config").unwrap();
+        let Value::Object(mut entries) = config else { unreachable!() };
+        entries[0].1 = Value::Int(5);
+        account.set_field("config", Value::Object(entries)).unwrap();
+        assert_eq!(account.as_ref().config().id(), 5);
+        assert_eq!(
+            account.get_field("history"),
+            Some(Value::List(vec![Value::Int(1), Value::Int(2)]))
+        );
+
+        let mut chance = Chance::new();
+        assert_eq!(chance.get_field("probability"), Some(Value::Int(75)));
+        chance.set_field("probability", Value::Int(5)).unwrap();
+        assert_eq!(chance.probability(), Some(5));
+        assert_eq!(Wallet::new().get_field("config"), Some(Value::Null));
+
+        // null clears optionals, so values with empty optionals round-trip
+        let mut reservation = Reservation::new();
+        reservation.set_field("seats", Value::Null).unwrap();
+        reservation.set_field("note", Value::String("window".into())).unwrap();
+        reservation
+            .set_field("config", Value::Object(vec![("id".into(), Value::Int(3))]))
+            .unwrap();
+        assert_eq!(reservation.seats(), None);
+        assert_eq!(reservation.note(), Some("window".to_string()));
+        assert_eq!(reservation.as_ref().config().unwrap().id(), 3);
+
+        let mut other = Reservation::new();
+        other.set_fields(reservation.to_value()).unwrap();
+        assert_eq!(other.to_value(), reservation.to_value());
+        let copy = justcxx::CppOwned::<Reservation>::from_value(other.to_value()).unwrap();
+        assert_eq!(copy.seats(), None);
+
+        other.clear_note();
+        other.set_field("config", Value::Null).unwrap();
+        assert_eq!(other.note(), None);
+        assert!(other.as_ref().config().is_none());
+
+        // fields bound without a getter are not reflected
+        let mut holder = PtrHolder::new();
+        assert_eq!(PtrHolder::FIELDS.len(), 1);
+        assert_eq!(holder.get_field("owned"), None);
+        assert_eq!(holder.set_field("owned", Value::Null), Err(FieldError::NotFound));
+        assert_eq!(holder.to_value(), Value::Object(vec![("id".into(), Value::Int(7))]));
+
+        let mut example = MapExample::new();
+        example
+            .set_field("str_int_map", Value::Map(vec![(Value::String("k".into()), Value::Int(2))]))
+            .unwrap();
+        assert_eq!(
+            example.get_field("str_int_map"),
+            Some(Value::Map(vec![(Value::String("k".into()), Value::Int(2))]))
+        );
+
+        let signal = Signal::new();
+        assert_eq!(
+            signal.as_ref().samples().to_value(),
+            Value::List(vec![Value::Float(1.5), Value::Float(0.0), Value::Float(0.0)])
+        );
+    }
 }
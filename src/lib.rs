@@ -11,6 +11,8 @@ pub trait CppClass {
 }
 pub trait Storage<T: CppClass> {
     type Inner;
+    /// # Safety
+    /// The returned pointer is only valid while `inner` is alive.
     unsafe fn as_ptr(inner: &Self::Inner) -> *mut T::FfiType;
 }
 
@@ -43,9 +45,205 @@ pub trait CppTypeAliases {
     type Mut<'a>;
 }
 
+/// Implemented by every bound `Vec<T>` and `Map<K, V>` view.
+pub trait CppContainer {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 pub type CppOwned<T> = <T as CppTypeAliases>::Owned;
 pub type CppRef<'a, T> = <T as CppTypeAliases>::Ref<'a>;
 pub type CppMut<'a, T> = <T as CppTypeAliases>::Mut<'a>;
 
+/// A C++ exception caught at the FFI boundary, as by `-> Result<Self>` constructors.
+pub type CppException = cxx::Exception;
+
+/// A bound field, as listed in the `FIELDS` constant of its class tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    /// The field type as written in `bind!`, e.g. `Vec<String>`.
+    pub ty: &'static str,
+    /// No setter: `#[readonly]`, a getter without a setter, or a `#[contiguous]` slice.
+    pub readonly: bool,
+    pub protected: bool,
+}
+
+/// A field value read or written by name through [`Reflect`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// An empty `Option`; writing it clears the field.
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    /// A `Vec`, a slice or a `#[contiguous]` class.
+    List(Vec<Value>),
+    /// A `Map` as key-value pairs.
+    Map(Vec<(Value, Value)>),
+    /// An object as its public fields, in declaration order.
+    Object(Vec<(String, Value)>),
+}
+
+/// Why [`Reflect::set_field`] failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    NotFound,
+    /// The field has no setter, or the object is behind a `CppRef`.
+    ReadOnly,
+    /// The value does not convert to the field type.
+    TypeMismatch,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FieldError::NotFound => "no such field",
+            FieldError::ReadOnly => "field is read-only",
+            FieldError::TypeMismatch => "value does not match the field type",
+        })
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Dynamic access to the bound fields of a class by name.
+pub trait Reflect {
+    fn fields(&self) -> &'static [FieldInfo];
+    fn get_field(&self, name: &str) -> Option<Value>;
+    /// Objects are updated field by field, everything else is replaced.
+    fn set_field(&mut self, name: &str, value: Value) -> Result<(), FieldError>;
+
+    /// Sets every field of a [`Value::Object`], skipping read-only ones.
+    fn set_fields(&mut self, value: Value) -> Result<(), FieldError> {
+        let Value::Object(entries) = value else {
+            return Err(FieldError::TypeMismatch);
+        };
+        for (name, value) in entries {
+            match self.set_field(&name, value) {
+                Err(FieldError::ReadOnly)
+                    if self.fields().iter().any(|f| f.name == name && f.readonly) => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub trait ToValue {
+    fn to_value(&self) -> Value;
+}
+
+/// `None` when the value does not convert to `Self`.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+macro_rules! impl_value_int {
+    ($variant:ident($wide:ty): $($t:ty),*) => {$(
+        impl ToValue for $t {
+            fn to_value(&self) -> Value {
+                Value::$variant(*self as $wide)
+            }
+        }
+
+        impl FromValue for $t {
+            fn from_value(value: Value) -> Option<Self> {
+                match value {
+                    Value::Int(v) => v.try_into().ok(),
+                    Value::UInt(v) => v.try_into().ok(),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+impl_value_int!(Int(i64): i8, i16, i32, i64, isize);
+impl_value_int!(UInt(u64): u8, u16, u32, u64, usize);
+
+macro_rules! impl_value_float {
+    ($($t:ty),*) => {$(
+        impl ToValue for $t {
+            fn to_value(&self) -> Value {
+                Value::Float(*self as f64)
+            }
+        }
+
+        impl FromValue for $t {
+            fn from_value(value: Value) -> Option<Self> {
+                match value {
+                    Value::Float(v) => Some(v as $t),
+                    Value::Int(v) => Some(v as $t),
+                    Value::UInt(v) => Some(v as $t),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+impl_value_float!(f32, f64);
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value {
+        Value::String(self.to_owned())
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, T::to_value)
+    }
+}
+
+impl<T: ToValue> ToValue for [T] {
+    fn to_value(&self) -> Value {
+        Value::List(self.iter().map(T::to_value).collect())
+    }
+}
+
+impl<T: ToValue + ?Sized> ToValue for &T {
+    fn to_value(&self) -> Value {
+        (**self).to_value()
+    }
+}
+
 pub use cxx;
+#[cfg(feature = "serde")]
+pub use serde;
 pub use justcxx_macro::bind;
\ No newline at end of file